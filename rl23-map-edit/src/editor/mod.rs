@@ -6,39 +6,112 @@ use retro_blit::{
     window::{KeyCode, RetroBlitContext, WindowMode}
 };
 use retro_blit::rendering::shapes::fill_rectangle;
-use rl23_map_format::{EntityDrawCommand, GatherableItem, MapEntity, TerrainKind, TilingInfo, WallKind, WangEncoding};
-use crate::editor::tool::EditorTool;
+use rl23_map_format::{map_rng, EntityDrawCommand, EntityId, GatherableItem, Grid, LightMap, MapEntity, LootTables, MapInfo, MapRng, MoveCost, MovementProfile, RegionId, ShadeRamps, TerrainKind, TilePos, Tileset, ValidationIssue, WallKind, BASE_MOVE_COST};
+use crate::editor::{resize::ResizeDialog, tool::EditorTool};
 
 const SCROLL_SPEED: f32 = 512.0;
 const TILES_BYTES: &[u8] = include_bytes!("../../../assets/tiles.im256");
 const JETBRAINS_MONO_FONT: &[u8] = include_bytes!("../../../assets/JetBrainsMono-Medium.ttf");
+const TILESET_PATH: &str = "assets/tileset.ron";
+const LOOT_TABLES_PATH: &str = "assets/loot_tables.ron";
 
+mod properties;
+mod resize;
 mod tool;
 
 struct EditorApp {
     palette: Vec<[u8; 3]>,
     sprite_sheet: BlittableSurface,
+    tileset: Tileset,
+    loot_tables: LootTables,
+    loot_tables_error: Option<String>,
     file_path: PathBuf,
-    map_info: rl23_map_format::MapInfo,
+    map_info: MapInfo,
+    /// Picks mud variants for the brush, seeded from the map so a session can be replayed.
+    rng: MapRng,
+    save_error: Option<String>,
+    placement_warning: Option<String>,
+    validation_issues: Option<Vec<ValidationIssue>>,
+    resize_dialog: Option<ResizeDialog>,
+    properties_open: bool,
+    /// Lines of the last loot table preview.
+    loot_preview: Option<Vec<String>>,
+    passability_overlay: Option<MovementProfile>,
+    light_preview: bool,
+    shade_ramps: ShadeRamps,
+    /// Overlays computed from the map, dropped by `map_changed` and rebuilt when drawn.
+    light_map: Option<LightMap>,
+    passability: Option<(MovementProfile, Grid<Option<MoveCost>>)>,
     current_terrain_kind: TerrainKind,
     current_wall_kind: Option<WallKind>,
     current_gatherable_kind: Option<GatherableItem>,
     current_entity_kind: Option<MapEntity>,
-    current_edited_entity: Option<usize>,
+    current_edited_entity: Option<EntityId>,
+    /// An inspector field waits for an entity to be clicked on the map.
+    entity_pick_pending: bool,
+    picked_entity: Option<EntityId>,
+    current_region: Option<RegionId>,
+    /// Tags text being typed for a region, parsed when the field loses focus or Enter is pressed.
+    region_tags: Option<(RegionId, String)>,
+    region_erase: bool,
     current_tool: EditorTool,
     mouse_pressed: bool,
     camera_x: f32,
     camera_y: f32,
-    entity_draw_queue: Vec<Vec<EntityDrawCommand>>
+    entity_draw_queue: Grid<Vec<EntityDrawCommand>>,
+    footprint_color: u8,
+    conflict_color: u8,
+    region_colors: Vec<u8>
 }
 
-pub fn open_for_edit(file_path: &PathBuf) {
+/// Index of the palette entry closest to `color`.
+fn closest_palette_index(palette: &[[u8; 3]], color: [u8; 3]) -> u8 {
+    let distance = |entry: &[u8; 3]| -> i32 {
+        (0..3).map(|i| (entry[i] as i32 - color[i] as i32).pow(2)).sum()
+    };
+    (0..palette.len()).min_by_key(|&i| distance(&palette[i])).unwrap_or(0) as u8
+}
+
+/// Loads the tileset manifest from the working directory, so sprites can be moved
+/// without rebuilding the editor. Falls back to the manifest built into the map format.
+fn load_tileset() -> Tileset {
+    let tileset_path: PathBuf = TILESET_PATH.into();
+    if !tileset_path.exists() {
+        return Tileset::default();
+    }
+    match Tileset::read_from_path(&tileset_path) {
+        Ok(tileset) => tileset,
+        Err(error) => {
+            eprintln!("failed to load {}: {}, using the built-in tileset", TILESET_PATH, error);
+            Tileset::default()
+        }
+    }
+}
+
+/// Shared loot tables, or none with the reason they could not be loaded,
+/// for instance tables referring to each other.
+fn load_loot_tables() -> (LootTables, Option<String>) {
+    let loot_tables_path: PathBuf = LOOT_TABLES_PATH.into();
+    if !loot_tables_path.exists() {
+        return (LootTables::default(), None);
+    }
+    match LootTables::read_from_path(&loot_tables_path) {
+        Ok(loot_tables) => (loot_tables, None),
+        Err(error) => {
+            let message = format!("failed to load {}: {}", LOOT_TABLES_PATH, error);
+            eprintln!("{}", message);
+            (LootTables::default(), Some(message))
+        }
+    }
+}
+
+pub fn open_for_edit(file_path: &PathBuf, map_info: MapInfo) {
     let file_path = file_path.clone();
-    retro_blit::window::start(EditorApp::new(file_path));
+    retro_blit::window::start(EditorApp::new(file_path, map_info));
 }
 
 impl EditorApp {
-    pub fn new(file_path: PathBuf) -> Self {
+    pub fn new(file_path: PathBuf, map_info: MapInfo) -> Self {
         let (mut palette, sprite_sheet) = retro_blit::format_loaders::im_256::Image::load_from(TILES_BYTES).unwrap();
         for pal in palette.iter_mut() {
             let r = pal[0] as f32;
@@ -54,13 +127,36 @@ impl EditorApp {
             *pal = [r, g, b];
         }
 
-        let map_info = rl23_map_format::MapInfo::read_from_path(&file_path);
-        let size = map_info.width * map_info.height;
+        let entity_draw_queue = Grid::new(map_info.width(), map_info.height(), Vec::with_capacity(4));
+        let footprint_color = closest_palette_index(&palette, [255, 255, 0]);
+        let conflict_color = closest_palette_index(&palette, [255, 0, 0]);
+        let shade_ramps = ShadeRamps::new(&palette);
+        let region_colors = [[255, 0, 255], [0, 255, 255], [0, 255, 0], [255, 128, 0], [128, 128, 255]]
+            .into_iter()
+            .map(|color| closest_palette_index(&palette, color))
+            .collect();
+        let rng = map_rng(map_info.metadata.seed);
+        let (loot_tables, loot_tables_error) = load_loot_tables();
         Self {
             palette,
             sprite_sheet,
+            tileset: load_tileset(),
+            loot_tables,
+            loot_tables_error,
             file_path,
             map_info,
+            rng,
+            save_error: None,
+            placement_warning: None,
+            validation_issues: None,
+            resize_dialog: None,
+            properties_open: false,
+            loot_preview: None,
+            passability_overlay: None,
+            light_preview: false,
+            shade_ramps,
+            light_map: None,
+            passability: None,
             current_tool: EditorTool::Terrain,
             current_terrain_kind: TerrainKind::Mud { offset: 0},
             current_wall_kind: Some(WallKind::Dirt),
@@ -70,10 +166,24 @@ impl EditorApp {
             camera_x: 0.0,
             camera_y: 0.0,
             current_edited_entity: None,
-            entity_draw_queue: vec![Vec::with_capacity(4); size]
+            entity_pick_pending: false,
+            picked_entity: None,
+            current_region: None,
+            region_tags: None,
+            region_erase: false,
+            entity_draw_queue,
+            footprint_color,
+            conflict_color,
+            region_colors
         }
     }
 
+    /// Drops the overlays computed from the map after an edit which may change them.
+    fn map_changed(&mut self) {
+        self.light_map = None;
+        self.passability = None;
+    }
+
     fn handle_keyboard_input(&mut self, ctx: &mut RetroBlitContext, dt: f32) {
         if ctx.is_egui_wants_keyboard_input() {
             return;
@@ -108,15 +218,52 @@ impl EditorApp {
 
     fn get_edited_entity_coords(&mut self) -> Option<(i32, i32)> {
         if let EditorTool::EditEntities = self.current_tool {
-            let idx = self.current_edited_entity?;
-            let x = idx % self.map_info.width;
-            let y = idx / self.map_info.width;
+            let id = self.current_edited_entity?;
+            let TilePos { x, y } = self.map_info.entities.get(id)?.position();
             Some((x as i32, y as i32))
         } else {
             None
         }
     }
 
+    /// Outlines the tiles `map_entity` would cover at `pos`.
+    fn draw_footprint_outline(
+        &self,
+        ctx: &mut RetroBlitContext,
+        map_entity: MapEntity,
+        pos: TilePos,
+        color: u8
+    ) {
+        let tiles: Vec<TilePos> = self.map_info
+            .footprint_tiles(map_entity, pos)
+            .map(|(tile, _)| tile)
+            .collect();
+        self.draw_tiles_outline(ctx, &tiles, color);
+    }
+
+    /// Draws the outer border of an area made of `tiles`, which must be in row-major order.
+    fn draw_tiles_outline(&self, ctx: &mut RetroBlitContext, tiles: &[TilePos], color: u8) {
+        let camera_x = self.camera_x as i32;
+        let camera_y = self.camera_y as i32;
+        for &tile in tiles.iter() {
+            let x = (tile.x as i32 * 32 - camera_x) as i16;
+            let y = (tile.y as i32 * 32 - camera_y) as i16;
+            let is_edge = |dx, dy| !matches!(tile.offset(dx, dy), Some(other) if tiles.binary_search(&other).is_ok());
+            if is_edge(0, -1) {
+                fill_rectangle(ctx, x, y, 32, 2, color);
+            }
+            if is_edge(0, 1) {
+                fill_rectangle(ctx, x, y + 30, 32, 2, color);
+            }
+            if is_edge(-1, 0) {
+                fill_rectangle(ctx, x, y, 2, 32, color);
+            }
+            if is_edge(1, 0) {
+                fill_rectangle(ctx, x + 30, y, 2, 32, color);
+            }
+        }
+    }
+
     fn get_selection_coords(&mut self, ctx: &mut RetroBlitContext) -> Option<(i32, i32)> {
         let (mouse_x, mouse_y) = ctx.get_mouse_pos();
         let rx = mouse_x + self.camera_x;
@@ -134,97 +281,23 @@ impl EditorApp {
         let camera_x = self.camera_x as i32;
         let camera_y = self.camera_y as i32;
 
-        // Render mud background. Any tile has it
-        {
-            for j in 0..self.map_info.height {
-                for i in 0..self.map_info.width {
-                    let terrain_kind = {
-                        let idx = self.map_info.width * j + i;
-                        match self.map_info.terrain_layer[idx] {
-                            TerrainKind::Mud { offset } => TerrainKind::Mud { offset },
-                            _ => TerrainKind::Mud { offset: 10 }
-                        }
-                    };
-                    let tiling_info = terrain_kind.get_tiling_info();
-                    match tiling_info {
-                        TilingInfo::Mud(mud_terrain) => {
-                            let [x, y] = mud_terrain.get_variant_coords_by_offset();
-                            BlitBuilder::create(ctx, &self.sprite_sheet.with_color_key(0))
-                                .with_source_subrect(x, y, 32, 32)
-                                .with_dest_pos(
-                                    (i as i32 * 32 - camera_x) as _,
-                                    (j as i32 * 32 - camera_y) as _
-                                ).blit();
-                        }
-                        _ => unreachable!()
-                    }
-                }
-            }
-        }
-
-        // Render terrain
+        // Render terrain: the blend base under every tile, then the other kinds in blend order
         {
-            for j in 0..=self.map_info.height {
-                for i in 0..=self.map_info.width {
-                    let i_w = if i == 0 { i } else { i - 1 };
-                    let i_e = if i >= self.map_info.width-1 { self.map_info.width-1 } else { i };
-
-                    let j_n = if j == 0 { j } else { j - 1 };
-                    let j_s = if j >= self.map_info.height-1 { self.map_info.height-1 } else { j };
-
-                    for kind in [
-                        TerrainKind::Sand,
-                        TerrainKind::Dirt,
-                        TerrainKind::Tile,
-                        TerrainKind::BrightTile,
-                        TerrainKind::MossTile,
-                        TerrainKind::VibrantTile,
-                        TerrainKind::Grass,
-                        TerrainKind::CaveWater,
-                        TerrainKind::Water,
-                        TerrainKind::Lava
-                    ] {
-                        let mut encoding = WangEncoding {
-                            north_east: false,
-                            north_west: false,
-                            south_east: false,
-                            south_west: false
-                        };
-                        if self.map_info.terrain_layer[i_e + j_n * self.map_info.width] == kind {
-                            encoding.north_east = true;
-                        }
-                        if self.map_info.terrain_layer[i_w + j_n * self.map_info.width] == kind {
-                            encoding.north_west = true;
-                        }
-                        if self.map_info.terrain_layer[i_w + j_s * self.map_info.width] == kind {
-                            encoding.south_west = true;
-                        }
-                        if self.map_info.terrain_layer[i_e + j_s * self.map_info.width] == kind {
-                            encoding.south_east = true;
-                        }
-
-                        match kind.get_tiling_info() {
-                            TilingInfo::Wang(wang) => {
-                                if let Some([x, y]) = wang.get_final_coords(encoding) {
-                                    BlitBuilder::create(ctx, &self.sprite_sheet.with_color_key(0))
-                                        .with_source_subrect(x, y, 32, 32)
-                                        .with_dest_pos((i as i32 * 32 - camera_x) as i16 - 16, (j as i32 * 32 - camera_y) as i16 -16)
-                                        .blit();
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-            }
+            self.tileset.for_each_terrain_sprite(&self.map_info, |[dest_x, dest_y], [x, y]| {
+                BlitBuilder::create(ctx, &self.sprite_sheet.with_color_key(0))
+                    .with_source_subrect(x, y, 32, 32)
+                    .with_dest_pos((dest_x as i32 - camera_x) as i16, (dest_y as i32 - camera_y) as i16)
+                    .blit();
+            });
         }
 
         // Render gatherables
         {
-            for (&idx, gatherable) in self.map_info.gatherable_layer.iter() {
-                let coord_x = idx % self.map_info.width;
-                let coord_y = idx / self.map_info.height;
-                let [source_x, source_y] = gatherable.get_coords();
+            for (TilePos { x: coord_x, y: coord_y }, gatherable) in self.map_info.gatherable_layer.iter() {
+                let [source_x, source_y] = match self.tileset.gatherable_coords(*gatherable) {
+                    Some(coords) => coords,
+                    None => continue
+                };
 
                 BlitBuilder::create(ctx, &self.sprite_sheet.with_color_key(0))
                     .with_source_subrect(source_x, source_y, 32, 32)
@@ -237,67 +310,37 @@ impl EditorApp {
 
         // Render walls
         {
-            for j in 0..=self.map_info.height {
-                for i in 0..=self.map_info.width {
-                    let i_w = if i == 0 { i } else { i - 1 };
-                    let i_e = if i >= self.map_info.width-1 { self.map_info.width-1 } else { i };
-
-                    let j_n = if j == 0 { j } else { j - 1 };
-                    let j_s = if j >= self.map_info.height-1 { self.map_info.height-1 } else { j };
-
-                    for kind in [
-                        WallKind::Dirt,
-                        WallKind::Bricks,
-                        WallKind::Wood
-                    ] {
-                        let mut encoding = WangEncoding {
-                            north_east: false,
-                            north_west: false,
-                            south_east: false,
-                            south_west: false
-                        };
-                        if self.map_info.wall_layer[i_e + j_n * self.map_info.width] == Some(kind) {
-                            encoding.north_east = true;
-                        }
-                        if self.map_info.wall_layer[i_w + j_n * self.map_info.width] == Some(kind) {
-                            encoding.north_west = true;
-                        }
-                        if self.map_info.wall_layer[i_w + j_s * self.map_info.width] == Some(kind) {
-                            encoding.south_west = true;
-                        }
-                        if self.map_info.wall_layer[i_e + j_s * self.map_info.width] == Some(kind) {
-                            encoding.south_east = true;
-                        }
-
-                        match kind.get_tiling_info() {
-                            TilingInfo::Wang(wang) => {
-                                if let Some([x, y]) = wang.get_final_coords(encoding) {
-                                    BlitBuilder::create(ctx, &self.sprite_sheet.with_color_key(0))
-                                        .with_source_subrect(x, y, 32, 32)
-                                        .with_dest_pos((i as i32 * 32 - camera_x) as i16 - 16, (j as i32 * 32 - camera_y) as i16 -16)
-                                        .blit();
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
+            for kind in [
+                WallKind::Dirt,
+                WallKind::Bricks,
+                WallKind::Wood
+            ] {
+                if let Some(autotile) = self.tileset.wall(kind) {
+                    autotile.for_each_sprite(&self.map_info.wall_layer, |&it| it == Some(kind), |[dest_x, dest_y], [x, y]| {
+                        BlitBuilder::create(ctx, &self.sprite_sheet.with_color_key(0))
+                            .with_source_subrect(x, y, 32, 32)
+                            .with_dest_pos((dest_x as i32 - camera_x) as i16, (dest_y as i32 - camera_y) as i16)
+                            .blit();
+                    });
                 }
             }
         }
 
         // Render entities
         {
-            for queue in self.entity_draw_queue.iter_mut() {
+            for (_, queue) in self.entity_draw_queue.iter_mut() {
                 queue.clear();
             }
-            for (&idx, map_entity) in self.map_info.entity_layer.iter() {
-                let command = map_entity.get_draw_command();
-                self.entity_draw_queue[idx].push(command);
+            for placed in self.map_info.entities.iter() {
+                if let (Some(command), Some(queue)) = (
+                    self.tileset.entity(placed.entity),
+                    self.entity_draw_queue.get_mut(placed.position())
+                ) {
+                    queue.push(command);
+                }
             }
 
-            for (idx, command_queue) in self.entity_draw_queue.iter_mut().enumerate() {
-                let coord_x = idx % self.map_info.width;
-                let coord_y = idx / self.map_info.height;
+            for (TilePos { x: coord_x, y: coord_y }, command_queue) in self.entity_draw_queue.iter_mut() {
                 command_queue.sort_by(|lhs, rhs| lhs.drawing_layer.cmp(&rhs.drawing_layer));
                 for cmd in command_queue.iter() {
                     let [source_x, source_y] = cmd.coords;
@@ -313,6 +356,74 @@ impl EditorApp {
             }
         }
 
+        // Render light preview: every pixel of a tile is darkened along its palette ramp, light sources get a mark in their colour
+        if self.light_preview {
+            let map_info = &self.map_info;
+            let light_map = self.light_map.get_or_insert_with(|| map_info.light_map());
+            let buffer_width = ctx.get_buffer_width() as i32;
+            let buffer_height = ctx.get_buffer_height() as i32;
+            let buffer = ctx.get_buffer_mut();
+            for (TilePos { x, y }, &level) in light_map.levels().iter() {
+                let min_x = (x as i32 * 32 - camera_x).clamp(0, buffer_width);
+                let max_x = (x as i32 * 32 + 32 - camera_x).clamp(0, buffer_width);
+                let min_y = (y as i32 * 32 - camera_y).clamp(0, buffer_height);
+                let max_y = (y as i32 * 32 + 32 - camera_y).clamp(0, buffer_height);
+                for row in min_y..max_y {
+                    let start = (row * buffer_width) as usize;
+                    for pixel in buffer[start + min_x as usize..start + max_x as usize].iter_mut() {
+                        *pixel = self.shade_ramps.shade(*pixel, level);
+                    }
+                }
+            }
+            for (TilePos { x, y }, light) in self.map_info.light_sources() {
+                let dest_x = (x as i32 * 32 - camera_x) as i16;
+                let dest_y = (y as i32 * 32 - camera_y) as i16;
+                fill_rectangle(ctx, dest_x + 13, dest_y + 13, 6, 6, light.color_index);
+            }
+        }
+
+        // Render regions: outlines in a colour per region, the selected one is marked on every tile
+        if self.current_tool == EditorTool::Regions {
+            for region in self.map_info.regions.iter() {
+                let color = self.region_colors[region.id as usize % self.region_colors.len()];
+                let tiles = self.map_info.tiles_in(region);
+                self.draw_tiles_outline(ctx, &tiles, color);
+                if self.current_region == Some(region.id) {
+                    for TilePos { x, y } in tiles {
+                        let dest_x = (x as i32 * 32 - camera_x) as i16;
+                        let dest_y = (y as i32 * 32 - camera_y) as i16;
+                        fill_rectangle(ctx, dest_x + 14, dest_y + 14, 4, 4, color);
+                    }
+                }
+            }
+        }
+
+        // Render passability overlay: impassable tiles get a red mark, costly ones a yellow mark
+        if let Some(profile) = self.passability_overlay {
+            if self.passability.as_ref().is_none_or(|(cached, _)| *cached != profile) {
+                self.passability = Some((profile, self.map_info.passability(profile)));
+            }
+            let (_, costs) = self.passability.as_ref().unwrap();
+            for (TilePos { x, y }, cost) in costs.iter() {
+                let color = match cost {
+                    None => self.conflict_color,
+                    Some(cost) if *cost > BASE_MOVE_COST => self.footprint_color,
+                    Some(_) => continue
+                };
+                let dest_x = (x as i32 * 32 - camera_x) as i16;
+                let dest_y = (y as i32 * 32 - camera_y) as i16;
+                fill_rectangle(ctx, dest_x + 12, dest_y + 12, 8, 8, color);
+            }
+        }
+
+        // Mark the player spawn point
+        if let Some(TilePos { x, y }) = self.map_info.metadata.player_spawn {
+            let dest_x = (x as i32 * 32 - camera_x) as i16;
+            let dest_y = (y as i32 * 32 - camera_y) as i16;
+            fill_rectangle(ctx, dest_x + 8, dest_y + 14, 16, 4, self.footprint_color);
+            fill_rectangle(ctx, dest_x + 14, dest_y + 8, 4, 16, self.footprint_color);
+        }
+
         // Draw selections
         {
             if let Some((entity_x, entity_y)) = self.get_edited_entity_coords() {
@@ -322,9 +433,24 @@ impl EditorApp {
                     .blit();
             }
 
+            if let (EditorTool::EditEntities, Some(id)) = (self.current_tool, self.current_edited_entity) {
+                if let Some(placed) = self.map_info.entities.get(id) {
+                    self.draw_footprint_outline(ctx, placed.entity, placed.position(), self.footprint_color);
+                }
+            }
+
             if let Some((coord_x, coord_y)) = self.get_selection_coords(ctx) {
-                if (0..self.map_info.width as i32).contains(&coord_x) &&
-                    (0..self.map_info.height as i32).contains(&coord_y) {
+                if (0..self.map_info.width() as i32).contains(&coord_x) &&
+                    (0..self.map_info.height() as i32).contains(&coord_y) {
+                    if let (EditorTool::Entities, Some(map_entity)) = (self.current_tool, self.current_entity_kind) {
+                        let pos = TilePos::new(coord_x as usize, coord_y as usize);
+                        let color = if self.map_info.placement_conflicts(map_entity, pos, None).is_empty() {
+                            self.footprint_color
+                        } else {
+                            self.conflict_color
+                        };
+                        self.draw_footprint_outline(ctx, map_entity, pos, color);
+                    }
                     BlitBuilder::create(ctx, &self.sprite_sheet.with_color_key(0))
                         .with_source_subrect(512, 288, 32, 32)
                         .with_dest_pos((coord_x * 32 - camera_x) as _, (coord_y * 32 - camera_y) as _)
@@ -335,13 +461,13 @@ impl EditorApp {
 
         let min_x = (-camera_x) as i16;
         let min_y = (-camera_y) as i16;
-        let max_x = (self.map_info.width as i32 * 32 - camera_x) as i16;
-        let max_y = (self.map_info.height as i32 * 32 - camera_y) as i16;
+        let max_x = (self.map_info.width() as i32 * 32 - camera_x) as i16;
+        let max_y = (self.map_info.height() as i32 * 32 - camera_y) as i16;
 
-        fill_rectangle(ctx, min_x - 16, min_y - 16, (self.map_info.width * 32 + 32) as u16, 16, 0);
-        fill_rectangle(ctx, min_x - 16, max_y, (self.map_info.width * 32 + 32) as u16, 16, 0);
-        fill_rectangle(ctx, min_x - 16, min_y, 16, self.map_info.height as u16 * 32, 0);
-        fill_rectangle(ctx, max_x, min_y, 16, self.map_info.height as u16 * 32, 0);
+        fill_rectangle(ctx, min_x - 16, min_y - 16, (self.map_info.width() * 32 + 32) as u16, 16, 0);
+        fill_rectangle(ctx, min_x - 16, max_y, (self.map_info.width() * 32 + 32) as u16, 16, 0);
+        fill_rectangle(ctx, min_x - 16, min_y, 16, self.map_info.height() as u16 * 32, 0);
+        fill_rectangle(ctx, max_x, min_y, 16, self.map_info.height() as u16 * 32, 0);
     }
 }
 
@@ -0,0 +1,82 @@
+use egui::{Align2, Context, Ui};
+use rl23_map_format::{Biome, TilePos};
+use crate::editor::EditorApp;
+
+/// Edits an optional name, an empty text meaning `None`.
+/// Spaces around the name are trimmed once the field loses focus, so spaces inside it can be typed.
+fn optional_text(ui: &mut Ui, label: &str, value: &mut Option<String>) {
+    let mut text = value.clone().unwrap_or_default();
+    ui.horizontal(|ui: &mut Ui| {
+        ui.label(label);
+        let response = ui.text_edit_singleline(&mut text);
+        if response.changed() {
+            *value = Some(text).filter(|text| !text.is_empty());
+        }
+        if response.lost_focus() {
+            *value = value.take().map(|text| text.trim().to_string()).filter(|text| !text.is_empty());
+        }
+    });
+}
+
+impl EditorApp {
+    pub fn properties_ui(&mut self, egui_ctx: &Context) {
+        if !self.properties_open {
+            return;
+        }
+        let mut close = false;
+        let bounds = self.map_info.bounds();
+        let metadata = &mut self.map_info.metadata;
+        egui::Window::new("Map properties")
+            .resizable(false)
+            .anchor(Align2::CENTER_TOP, [0.0, 0.0])
+            .show(egui_ctx, |ui: &mut Ui| {
+                ui.horizontal(|ui: &mut Ui| {
+                    ui.label("name:");
+                    ui.text_edit_singleline(&mut metadata.name);
+                });
+                ui.horizontal(|ui: &mut Ui| {
+                    ui.label("author:");
+                    ui.text_edit_singleline(&mut metadata.author);
+                });
+                ui.label("description:");
+                ui.text_edit_multiline(&mut metadata.description);
+
+                ui.label("biome:");
+                ui.horizontal_wrapped(|ui: &mut Ui| {
+                    for biome in Biome::ALL {
+                        ui.radio_value(&mut metadata.biome, biome, format!("{:?}", biome));
+                    }
+                });
+                optional_text(ui, "ambient palette:", &mut metadata.ambient_palette);
+                optional_text(ui, "music cue:", &mut metadata.music_cue);
+                ui.add(
+                    egui::DragValue::new(&mut metadata.recommended_level)
+                        .prefix("recommended level: ")
+                        .speed(1.0)
+                );
+
+                ui.label(format!("seed: {}", metadata.seed));
+
+                let mut has_spawn = metadata.player_spawn.is_some();
+                ui.checkbox(&mut has_spawn, "player spawn");
+                metadata.player_spawn = match (has_spawn, metadata.player_spawn) {
+                    (false, _) => None,
+                    (true, None) => Some(TilePos::new(0, 0)),
+                    (true, Some(mut spawn)) => {
+                        ui.horizontal(|ui: &mut Ui| {
+                            ui.add(egui::DragValue::new(&mut spawn.x).prefix("x: ").clamp_range(0..=bounds.width.saturating_sub(1)));
+                            ui.add(egui::DragValue::new(&mut spawn.y).prefix("y: ").clamp_range(0..=bounds.height.saturating_sub(1)));
+                        });
+                        Some(spawn)
+                    }
+                };
+
+                if ui.button("Close").clicked() {
+                    close = true;
+                }
+            });
+        if close {
+            self.properties_open = false;
+        }
+    }
+}
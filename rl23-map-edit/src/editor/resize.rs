@@ -0,0 +1,94 @@
+use egui::{Align2, Context, Ui};
+use rl23_map_format::{Grid, ResizeAnchor};
+use crate::editor::EditorApp;
+
+pub struct ResizeDialog {
+    width: usize,
+    height: usize,
+    anchor: ResizeAnchor,
+    report: Option<String>
+}
+
+impl EditorApp {
+    pub fn open_resize_dialog(&mut self) {
+        self.resize_dialog = Some(ResizeDialog {
+            width: self.map_info.width(),
+            height: self.map_info.height(),
+            anchor: ResizeAnchor::TopLeft,
+            report: None
+        });
+    }
+
+    pub fn resize_ui(&mut self, egui_ctx: &Context) {
+        let mut close = false;
+        let mut apply = false;
+        if let Some(dialog) = &mut self.resize_dialog {
+            egui::Window::new("resize")
+                .resizable(false)
+                .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(egui_ctx, |ui: &mut Ui| {
+                    ui.add(egui::DragValue::new(&mut dialog.width).prefix("width: ").clamp_range(1..=1024));
+                    ui.add(egui::DragValue::new(&mut dialog.height).prefix("height: ").clamp_range(1..=1024));
+                    ui.label("anchor:");
+                    for row in ResizeAnchor::ALL.chunks(3) {
+                        ui.horizontal(|ui: &mut Ui| {
+                            for &anchor in row {
+                                ui.radio_value(&mut dialog.anchor, anchor, anchor.name());
+                            }
+                        });
+                    }
+                    if let Some(report) = &dialog.report {
+                        ui.label(report);
+                    }
+                    ui.horizontal(|ui: &mut Ui| {
+                        if ui.button("Apply").clicked() {
+                            apply = true;
+                        }
+                        if ui.button("Close").clicked() {
+                            close = true;
+                        }
+                    });
+                });
+        }
+
+        if apply {
+            self.apply_resize();
+        }
+        if close {
+            self.resize_dialog = None;
+        }
+    }
+
+    fn apply_resize(&mut self) {
+        let dialog = match &mut self.resize_dialog {
+            Some(dialog) => dialog,
+            None => return
+        };
+        let report = match self.map_info.resize(dialog.width, dialog.height, dialog.anchor, &mut self.rng) {
+            Ok(report) => report,
+            Err(error) => {
+                dialog.report = Some(error.to_string());
+                return;
+            }
+        };
+
+        let mut lines = Vec::new();
+        for dropped in report.dropped_entities.iter() {
+            lines.push(format!(
+                "dropped {:?} {} at ({}, {})",
+                dropped.entity, dropped.id(), dropped.position().x, dropped.position().y
+            ));
+        }
+        if report.dropped_gatherables > 0 {
+            lines.push(format!("dropped {} gatherables", report.dropped_gatherables));
+        }
+        if lines.is_empty() {
+            lines.push("resized, nothing was dropped".to_string());
+        }
+        dialog.report = Some(lines.join("\n"));
+
+        self.current_edited_entity = None;
+        self.entity_draw_queue = Grid::new(self.map_info.width(), self.map_info.height(), Vec::with_capacity(4));
+        self.map_changed();
+    }
+}
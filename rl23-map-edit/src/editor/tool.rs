@@ -1,7 +1,6 @@
 use egui::{Align2, CollapsingHeader, Context, Ui};
-use rand::Rng;
 use retro_blit::window::RetroBlitContext;
-use rl23_map_format::{ClosedDoor, Decor, EntityComponentData, EntityComponentDataList, GatherableItem, MapEntity, TerrainKind, Tree, Unit, WallKind};
+use rl23_map_format::{complete_entity_pick, entity_pick_pending, map_rng, ClosedDoor, Decor, EntityComponentData, EntityId, GatherableItem, LootTable, LootTables, MapEntity, MovementProfile, PlacedEntity, PlacementConflict, Region, RegionShape, Severity, TerrainKind, TilePos, Tree, Unit, WallKind, MAX_LIGHT_LEVEL};
 use crate::editor::EditorApp;
 
 #[derive(Copy, Clone, PartialEq)]
@@ -10,7 +9,44 @@ pub enum EditorTool {
     Gatherables,
     Entities,
     EditEntities,
-    Walls
+    Walls,
+    Regions
+}
+
+/// Tags of a comma separated list, without surrounding spaces and empty tags.
+fn parse_tags(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+/// Number of times a loot table is rolled for its preview.
+const LOOT_PREVIEW_ROLLS: usize = 1000;
+
+/// Drop frequencies of `table` over many rolls, one line per item.
+fn loot_preview(table: &LootTable, loot_tables: &LootTables, seed: u64) -> Vec<String> {
+    if let Err(error) = loot_tables.validate_table_references(table) {
+        return vec![error.to_string()];
+    }
+    match table.drop_frequencies(loot_tables, &mut map_rng(seed), LOOT_PREVIEW_ROLLS) {
+        Ok(frequencies) if frequencies.is_empty() => vec!["nothing drops".to_string()],
+        Ok(frequencies) => frequencies
+            .iter()
+            .map(|frequency| format!(
+                "{:<20} {:>5.1}%  x{:.2}",
+                frequency.item,
+                frequency.chance * 100.0,
+                frequency.average_quantity
+            ))
+            .collect(),
+        Err(error) => vec![error.to_string()]
+    }
+}
+
+fn placement_warning(map_entity: MapEntity, conflicts: &[PlacementConflict]) -> String {
+    let reasons: Vec<String> = conflicts.iter().map(|conflict| conflict.to_string()).collect();
+    format!("Can't place {:?}: {}", map_entity, reasons.join(", "))
 }
 
 impl EditorApp {
@@ -18,108 +54,242 @@ impl EditorApp {
         if x < 0 || y < 0 {
             return;
         }
-        let x = x as usize;
-        let y = y as usize;
+        let pos = TilePos::new(x as usize, y as usize);
 
-        if x >= self.map_info.width {
+        if !self.map_info.bounds().contains(pos) {
             return;
         }
 
-        if y >= self.map_info.height {
+        // While an inspector waits for a target, clicks pick entities instead of editing the map.
+        if self.entity_pick_pending {
+            if let Some(&id) = self.map_info.entities.ids_at(pos).first() {
+                self.picked_entity = Some(id);
+                self.mouse_pressed = false;
+            }
             return;
         }
 
-        let idx = y * self.map_info.width + x;
-
         match self.current_tool {
             EditorTool::Terrain => {
-                self.map_info.terrain_layer[idx] = self.current_terrain_kind;
+                self.map_info.terrain_layer.set(pos, self.current_terrain_kind);
+                self.map_changed();
                 match self.current_terrain_kind {
                     TerrainKind::Mud { .. } => {
-                        let mut rng = rand::thread_rng();
-                        self.current_terrain_kind = TerrainKind::Mud {
-                            offset: rng.gen_range(0..12)
-                        };
+                        self.current_terrain_kind = TerrainKind::random_mud(&mut self.rng);
                     }
                     _ => {}
                 }
             }
             EditorTool::Entities => {
-                match self.map_info.entity_layer.get(&idx) {
+                match self.current_entity_kind {
                     None => {
-                        match self.current_entity_kind {
-                            None => {}
-                            Some(map_entity) => {
-                                self.map_info.entity_layer.insert(idx, map_entity);
-                                let id = self.map_info.id_generator.generate();
-                                self.map_info.entity_data_layer.insert(
-                                    idx,
-                                    EntityComponentDataList::create(id)
-                                );
+                        for id in self.map_info.entities.ids_at(pos).to_vec() {
+                            self.map_info.entities.remove(id);
+                            if self.current_edited_entity == Some(id) {
+                                self.current_edited_entity = None;
                             }
+                            self.map_changed();
                         }
                     }
-                    Some(&old_entity) => {
-                        match self.current_entity_kind {
-                            None => {
-                                self.map_info.entity_layer.remove(&idx);
-                                self.map_info.entity_data_layer.remove(&idx);
-                                match self.current_edited_entity {
-                                    Some(id) if id == idx => {
-                                        self.current_edited_entity = None;
-                                    },
-                                    _ => {}
-                                }
-                            }
-                            Some(map_entity) => {
-                                if old_entity != map_entity {
-                                    self.map_info.entity_layer.insert(idx, map_entity);
-                                    let id = self.map_info.id_generator.generate();
-                                    self.map_info.entity_data_layer.insert(
-                                        idx,
-                                        EntityComponentDataList::create(id)
-                                    );
-                                }
+                    Some(map_entity) => {
+                        let already_placed = self.map_info.entities
+                            .at(pos)
+                            .any(|placed| placed.entity == map_entity);
+                        if !already_placed {
+                            let conflicts = self.map_info.placement_conflicts(map_entity, pos, None);
+                            if conflicts.is_empty() {
+                                let id = self.map_info.id_generator.generate();
+                                self.map_info.entities.insert(PlacedEntity::new(id, pos, map_entity));
+                                self.placement_warning = None;
+                                self.map_changed();
+                            } else {
+                                self.placement_warning = Some(placement_warning(map_entity, &conflicts));
                             }
                         }
                     }
                 }
             }
             EditorTool::Walls => {
-                self.map_info.wall_layer[idx] = self.current_wall_kind;
+                self.map_info.wall_layer.set(pos, self.current_wall_kind);
+                self.map_changed();
             }
             EditorTool::Gatherables => {
-                match self.map_info.gatherable_layer.get(&idx) {
+                match self.map_info.gatherable_layer.get(pos) {
                     None => {
                         match self.current_gatherable_kind {
                             None => {}
                             Some(gatherable) => {
-                                self.map_info.gatherable_layer.insert(idx, gatherable);
+                                self.map_info.gatherable_layer.insert(pos, gatherable);
                             }
                         }
                     }
                     Some(&_) => {
                         match self.current_gatherable_kind {
                             None => {
-                                self.map_info.gatherable_layer.remove(&idx);
+                                self.map_info.gatherable_layer.remove(pos);
                             }
                             Some(gatherable) => {
-                                self.map_info.gatherable_layer.insert(idx, gatherable);
+                                self.map_info.gatherable_layer.insert(pos, gatherable);
                             }
                         }
                     }
                 }
             }
+            EditorTool::Regions => {
+                let erase = self.region_erase;
+                let bounds = self.map_info.bounds();
+                if let Some(region) = self.current_region.and_then(|id| self.map_info.region_mut(id)) {
+                    if erase {
+                        region.shape.remove(pos, bounds);
+                    } else {
+                        region.shape.insert(pos, bounds);
+                    }
+                }
+            }
             EditorTool::EditEntities => {
-                self.current_edited_entity = if self.map_info.entity_layer.contains_key(&idx) {
-                    Some(idx)
-                } else {
-                    None
-                };
+                let ids = self.map_info.entities.ids_at(pos);
+                let keep_selection = matches!(self.current_edited_entity, Some(id) if ids.contains(&id));
+                if !keep_selection {
+                    self.current_edited_entity = ids.first().copied();
+                }
             }
         }
     }
+
+    fn regions_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal_wrapped(|ui: &mut Ui| {
+            for region in self.map_info.regions.iter() {
+                let label = format!("{} {}", region.id, region.name);
+                ui.selectable_value(&mut self.current_region, Some(region.id), label);
+            }
+        });
+        if ui.button("New Region").clicked() {
+            let id = self.map_info.id_generator.generate();
+            let name = format!("region {}", id);
+            self.map_info.regions.push(Region::new(id, name, RegionShape::Tiles(Default::default())));
+            self.current_region = Some(id);
+        }
+
+        let id = match self.current_region {
+            Some(id) if self.map_info.region(id).is_some() => id,
+            _ => return
+        };
+        ui.separator();
+        ui.horizontal(|ui: &mut Ui| {
+            ui.radio_value(&mut self.region_erase, false, "Paint");
+            ui.radio_value(&mut self.region_erase, true, "Erase");
+        });
+
+        // Tags typed for another region are kept when the selection moves on before they are committed
+        if let Some((tags_id, tags)) = self.region_tags.take() {
+            if tags_id == id {
+                self.region_tags = Some((tags_id, tags));
+            } else if let Some(region) = self.map_info.regions.iter_mut().find(|region| region.id == tags_id) {
+                region.tags = parse_tags(&tags);
+            }
+        }
+
+        let id_generator = &mut self.map_info.id_generator;
+        let region = self.map_info.regions.iter_mut().find(|region| region.id == id).unwrap();
+        ui.text_edit_singleline(&mut region.name);
+        let (_, tags) = self.region_tags.get_or_insert_with(|| (id, region.tags.join(", ")));
+        let mut commit_tags = false;
+        ui.horizontal(|ui: &mut Ui| {
+            ui.label("tags:");
+            commit_tags = ui.text_edit_singleline(tags).lost_focus();
+        });
+        if commit_tags {
+            region.tags = parse_tags(tags);
+            self.region_tags = None;
+        }
+        ui.checkbox(&mut region.exclusive, "exclusive");
+
+        // Regions take the same components as logic entities
+        CollapsingHeader::new("components")
+            .id_source(("region", id))
+            .show(ui, |ui: &mut Ui| {
+                let mut offset = 0;
+                while offset < region.components.len() {
+                    if region.components[offset].draw_egui(id_generator, ui) {
+                        offset += 1;
+                    } else {
+                        region.components.remove(offset);
+                    }
+                }
+                if let Some(new_entry) = EntityComponentData::draw_context_menu(MapEntity::Logic, id_generator, ui) {
+                    region.components.push(new_entry);
+                }
+            });
+
+        if ui.button("Delete Region").clicked() {
+            self.map_info.regions.retain(|region| region.id != id);
+            self.current_region = None;
+        }
+    }
+
+    fn validation_ui(&mut self, egui_ctx: &Context) {
+        let mut close = false;
+        if let Some(issues) = &self.validation_issues {
+            egui::Window::new("validation")
+                .default_width(300.0)
+                .anchor(Align2::LEFT_BOTTOM, [0.0, 0.0])
+                .show(egui_ctx, |ui: &mut Ui| {
+                    if issues.is_empty() {
+                        ui.label("No issues found");
+                    }
+                    egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui: &mut Ui| {
+                        for issue in issues.iter() {
+                            let color = match issue.severity {
+                                Severity::Warning => egui::Color32::YELLOW,
+                                Severity::Error => egui::Color32::RED
+                            };
+                            let text = egui::RichText::new(issue.to_string()).color(color);
+                            if ui.add(egui::Button::new(text).frame(false)).clicked() {
+                                if let Some(TilePos { x, y }) = issue.position {
+                                    self.camera_x = (x * 32) as f32 - 480.0;
+                                    self.camera_y = (y * 32) as f32 - 300.0;
+                                }
+                            }
+                        }
+                    });
+                    if ui.button("Close").clicked() {
+                        close = true;
+                    }
+                });
+        }
+        if close {
+            self.validation_issues = None;
+        }
+    }
+
+    fn loot_preview_ui(&mut self, egui_ctx: &Context) {
+        let mut close = false;
+        if let Some(lines) = &self.loot_preview {
+            egui::Window::new("loot preview")
+                .default_width(300.0)
+                .anchor(Align2::CENTER_BOTTOM, [0.0, 0.0])
+                .show(egui_ctx, |ui: &mut Ui| {
+                    egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui: &mut Ui| {
+                        for line in lines.iter() {
+                            ui.label(line);
+                        }
+                    });
+                    if ui.button("Close").clicked() {
+                        close = true;
+                    }
+                });
+        }
+        if close {
+            self.loot_preview = None;
+        }
+    }
+
     pub fn tools_ui(&mut self, ctx: &mut RetroBlitContext, egui_ctx: &Context) {
+        if let Some(id) = self.picked_entity.take() {
+            complete_entity_pick(egui_ctx, id);
+        }
+        self.entity_pick_pending = entity_pick_pending(egui_ctx);
+
         egui::Window::new("general")
             .default_width(130.0)
             .resizable(false)
@@ -130,10 +300,52 @@ impl EditorApp {
                 ui.radio_value(&mut self.current_tool, EditorTool::Entities, "Entities");
                 ui.radio_value(&mut self.current_tool, EditorTool::EditEntities, "Edit Entities");
                 ui.radio_value(&mut self.current_tool, EditorTool::Walls, "Walls");
+                ui.radio_value(&mut self.current_tool, EditorTool::Regions, "Regions");
 
                 ui.separator();
                 if ui.button("Save").clicked() {
-                    self.map_info.save_to_path(&self.file_path);
+                    self.save_error = self.map_info
+                        .save_to_path(&self.file_path)
+                        .err()
+                        .map(|error| format!("Save failed: {}", error));
+                }
+                if let Some(loot_tables_error) = &self.loot_tables_error {
+                    ui.colored_label(egui::Color32::RED, loot_tables_error);
+                }
+                if let Some(save_error) = &self.save_error {
+                    ui.colored_label(egui::Color32::RED, save_error);
+                }
+                if let Some(placement_warning) = &self.placement_warning {
+                    ui.colored_label(egui::Color32::YELLOW, placement_warning);
+                }
+                if ui.button("Validate").clicked() {
+                    self.validation_issues = Some(self.map_info.validate());
+                }
+                if ui.button("Resize").clicked() {
+                    self.open_resize_dialog();
+                }
+                if ui.button("Map properties").clicked() {
+                    self.properties_open = true;
+                }
+
+                ui.separator();
+                ui.label("Passability");
+                ui.radio_value(&mut self.passability_overlay, None, "Off");
+                ui.radio_value(&mut self.passability_overlay, Some(MovementProfile::Walker), "Walker");
+                ui.radio_value(&mut self.passability_overlay, Some(MovementProfile::Swimmer), "Swimmer");
+                ui.radio_value(&mut self.passability_overlay, Some(MovementProfile::Flyer), "Flyer");
+                ui.radio_value(&mut self.passability_overlay, Some(MovementProfile::WallPasser), "Wall Passer");
+
+                ui.separator();
+                ui.label("Light");
+                ui.checkbox(&mut self.light_preview, "Preview");
+                let ambient_light = ui.add(
+                    egui::DragValue::new(&mut self.map_info.ambient_light)
+                        .prefix("ambient: ")
+                        .clamp_range(0..=MAX_LIGHT_LEVEL)
+                );
+                if ambient_light.changed() {
+                    self.map_changed();
                 }
 
                 ui.separator();
@@ -142,12 +354,18 @@ impl EditorApp {
                 }
             });
 
+        self.validation_ui(egui_ctx);
+        self.loot_preview_ui(egui_ctx);
+        self.resize_ui(egui_ctx);
+        self.properties_ui(egui_ctx);
+
         let tool_title = match self.current_tool {
             EditorTool::Terrain => "Brush                  ",
             EditorTool::Gatherables => "Brush                  ",
             EditorTool::Entities => "Brush                  ",
             EditorTool::EditEntities => "Inspector            ",
-            EditorTool::Walls => "Brush                  "
+            EditorTool::Walls => "Brush                  ",
+            EditorTool::Regions => "Regions                "
         };
 
         egui::Window::new(tool_title)
@@ -465,40 +683,82 @@ impl EditorApp {
                             None => {}
                         }
                     }
+                    EditorTool::Regions => self.regions_ui(ui),
                     EditorTool::EditEntities => {
-                        if let Some(idx) = self.current_edited_entity {
+                        if let Some(id) = self.current_edited_entity {
                             egui::ScrollArea::vertical().auto_shrink([false, true]).show(ui, |ui: &mut Ui| {
-                                let map_entity = self.map_info.entity_layer[&idx];
-                                if !self.map_info.entity_data_layer.contains_key(&idx) {
-                                    let id = self.map_info.id_generator.generate();
-                                    self.map_info.entity_data_layer.insert(
-                                        idx,
-                                        EntityComponentDataList::create(id)
-                                    );
+                                let position = match self.map_info.entities.get(id) {
+                                    Some(placed) => placed.position(),
+                                    None => return
+                                };
+                                let neighbours: Vec<(EntityId, MapEntity)> = self.map_info.entities
+                                    .at(position)
+                                    .map(|placed| (placed.id(), placed.entity))
+                                    .collect();
+                                if neighbours.len() > 1 {
+                                    ui.horizontal_wrapped(|ui: &mut Ui| {
+                                        for (other_id, other_entity) in neighbours {
+                                            let label = format!("{} {:?}", other_id, other_entity);
+                                            ui.selectable_value(&mut self.current_edited_entity, Some(other_id), label);
+                                        }
+                                    });
+                                    ui.separator();
                                 }
-                                match self.map_info.entity_data_layer.get_mut(&idx) {
-                                    Some(entries) => {
-                                        CollapsingHeader::new("components")
-                                            .id_source(entries.id())
-                                            .show(ui, |ui: &mut Ui| {
-                                                let mut offset = 0;
-                                                while offset < entries.len() {
-                                                    if entries[offset].draw_egui(&mut self.map_info.id_generator, ui) {
-                                                        offset += 1;
-                                                    } else {
-                                                        entries.remove(offset);
-                                                    }
-                                                }
-                                                if let Some(new_entry) = EntityComponentData::draw_context_menu(
-                                                    map_entity,
-                                                    &mut self.map_info.id_generator,
-                                                    ui
-                                                ) {
-                                                    entries.push(new_entry);
+
+                                let mut new_position = position;
+                                ui.horizontal(|ui: &mut Ui| {
+                                    ui.add(egui::DragValue::new(&mut new_position.x).prefix("x: ").speed(1.0));
+                                    ui.add(egui::DragValue::new(&mut new_position.y).prefix("y: ").speed(1.0));
+                                });
+                                if new_position != position {
+                                    let map_entity = self.map_info.entities.get(id).unwrap().entity;
+                                    let conflicts = self.map_info.placement_conflicts(map_entity, new_position, Some(id));
+                                    if conflicts.is_empty() {
+                                        self.map_info.entities.move_to(id, new_position);
+                                        self.placement_warning = None;
+                                        self.map_changed();
+                                    } else {
+                                        self.placement_warning = Some(placement_warning(map_entity, &conflicts));
+                                    }
+                                }
+
+                                let id = match self.current_edited_entity {
+                                    Some(id) => id,
+                                    None => return
+                                };
+                                let placed = self.map_info.entities.get_mut(id).unwrap();
+                                let map_entity = placed.entity;
+                                let components_before = placed.components.clone();
+                                CollapsingHeader::new("components")
+                                    .id_source(id)
+                                    .show(ui, |ui: &mut Ui| {
+                                        let mut offset = 0;
+                                        while offset < placed.components.len() {
+                                            if let EntityComponentData::LootTable(table) = &placed.components[offset] {
+                                                if ui.button("Preview drops").clicked() {
+                                                    self.loot_preview = Some(loot_preview(
+                                                        table,
+                                                        &self.loot_tables,
+                                                        self.map_info.metadata.seed
+                                                    ));
                                                 }
-                                            });
-                                    },
-                                    None => unreachable!()
+                                            }
+                                            if placed.components[offset].draw_egui(&mut self.map_info.id_generator, ui) {
+                                                offset += 1;
+                                            } else {
+                                                placed.components.remove(offset);
+                                            }
+                                        }
+                                        if let Some(new_entry) = EntityComponentData::draw_context_menu(
+                                            map_entity,
+                                            &mut self.map_info.id_generator,
+                                            ui
+                                        ) {
+                                            placed.components.push(new_entry);
+                                        }
+                                    });
+                                if placed.components != components_before {
+                                    self.map_changed();
                                 }
                             });
                         }
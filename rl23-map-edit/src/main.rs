@@ -1,15 +1,29 @@
-use std::path::PathBuf;
+use std::{num::NonZeroUsize, path::PathBuf};
+use rl23_map_format::{map_rng, MapInfo, ResizeAnchor, CURRENT_FORMAT_VERSION};
 
 const USAGES_STR: &str = include_str!("usages.txt");
 
 mod editor;
 
+/// Parses map dimensions, neither of which may be zero.
+fn parse_size(width: &str, height: &str) -> Option<(usize, usize)> {
+    match (width.parse::<NonZeroUsize>(), height.parse::<NonZeroUsize>()) {
+        (Ok(width), Ok(height)) => Some((width.get(), height.get())),
+        _ => None
+    }
+}
+
+/// Prints `message` to stderr and exits with a failure status, so scripts notice.
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1)
+}
+
 fn main() {
     let args: Vec<_> = std::env::args().collect();
 
-    if !([3, 5].contains(&args.len())) {
-        println!("{}", USAGES_STR);
-        return;
+    if !([3, 4, 5, 6, 7].contains(&args.len())) {
+        fail(USAGES_STR);
     }
 
     let command = args[1].clone();
@@ -18,24 +32,126 @@ fn main() {
         "--edit" => {
             let ron_file_name = args[2].clone();
             let ron_path: PathBuf = (&ron_file_name).into();
-            editor::open_for_edit(&ron_path);
+            match MapInfo::read_from_path(&ron_path) {
+                Ok(map) => editor::open_for_edit(&ron_path, map),
+                Err(error) => fail(&format!("failed to open {}: {}", ron_file_name, error))
+            }
         }
         "--create" => {
             let ron_file_name = args[2].clone();
             let ron_path: PathBuf = (&ron_file_name).into();
-            if args.len() != 5 {
-                println!("{}", USAGES_STR);
-                return;
-            }
-            let width: usize = args[3].parse().unwrap();
-            let height: usize = args[4].parse().unwrap();
-            let map = rl23_map_format::MapInfo::create_new(width, height);
-            map.save_to_path(&ron_path);
-            editor::open_for_edit(&ron_path);
+            if args.len() != 5 && args.len() != 7 {
+                fail(USAGES_STR);
+            }
+            let (width, height) = match parse_size(&args[3], &args[4]) {
+                Some(size) => size,
+                None => fail(USAGES_STR)
+            };
+            let seed: u64 = match (args.get(5).map(|arg| arg.as_str()), args.get(6).map(|arg| arg.parse())) {
+                (None, None) => {
+                    let seed = rand::random();
+                    println!("seed: {}", seed);
+                    seed
+                }
+                (Some("--seed"), Some(Ok(seed))) => seed,
+                _ => fail(USAGES_STR)
+            };
+            let map = MapInfo::create_new(width, height, seed);
+            if let Err(error) = map.save_to_path(&ron_path) {
+                fail(&format!("failed to create {}: {}", ron_file_name, error));
+            }
+            editor::open_for_edit(&ron_path, map);
+        }
+        "--resize" => {
+            let ron_file_name = args[2].clone();
+            let ron_path: PathBuf = (&ron_file_name).into();
+            if args.len() < 5 {
+                fail(USAGES_STR);
+            }
+            let (width, height) = match parse_size(&args[3], &args[4]) {
+                Some(size) => size,
+                None => fail(USAGES_STR)
+            };
+            let anchor = match args.get(5).map(|arg| arg.parse()) {
+                None => ResizeAnchor::TopLeft,
+                Some(Ok(anchor)) => anchor,
+                Some(Err(_)) => fail(USAGES_STR)
+            };
+            let mut map = match MapInfo::read_from_path(&ron_path) {
+                Ok(map) => map,
+                Err(error) => fail(&format!("failed to open {}: {}", ron_file_name, error))
+            };
+            let mut rng = map_rng(map.metadata.seed);
+            let report = map
+                .resize(width, height, anchor, &mut rng)
+                .unwrap_or_else(|error| fail(&format!("failed to resize {}: {}", ron_file_name, error)));
+            for dropped in report.dropped_entities.iter() {
+                println!(
+                    "dropped {:?} {} at ({}, {})",
+                    dropped.entity, dropped.id(), dropped.position().x, dropped.position().y
+                );
+            }
+            if report.dropped_gatherables > 0 {
+                println!("dropped {} gatherables", report.dropped_gatherables);
+            }
+            if let Err(error) = map.save_to_path(&ron_path) {
+                fail(&format!("failed to save {}: {}", ron_file_name, error));
+            }
+        }
+        "--convert" => {
+            if args.len() != 4 {
+                fail(USAGES_STR);
+            }
+            let source_path: PathBuf = (&args[2]).into();
+            let target_path: PathBuf = (&args[3]).into();
+            let map = match MapInfo::read_from_path(&source_path) {
+                Ok(map) => map,
+                Err(error) => fail(&format!("failed to open {}: {}", args[2], error))
+            };
+            if let Err(error) = map.save_to_path(&target_path) {
+                fail(&format!("failed to save {}: {}", args[3], error));
+            }
+        }
+        "--migrate" => {
+            let dir_name = args[2].clone();
+            if !migrate_directory(&dir_name.into()) {
+                std::process::exit(1);
+            }
+        }
+        _ => fail(USAGES_STR)
+    }
+}
+
+/// Migrates every map of the directory, returns whether all of them could be migrated.
+fn migrate_directory(dir_path: &PathBuf) -> bool {
+    let entries = match std::fs::read_dir(dir_path) {
+        Ok(entries) => entries,
+        Err(error) => {
+            eprintln!("failed to read {}: {}", dir_path.display(), error);
+            return false;
         }
-        _ => {
-            println!("{}", USAGES_STR);
-            return;
+    };
+    let mut map_paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().map_or(false, |ext| ext == "ron"))
+        .collect();
+    map_paths.sort();
+
+    let mut all_migrated = true;
+    for map_path in map_paths {
+        match MapInfo::migrate_file(&map_path) {
+            Ok(version) if version == CURRENT_FORMAT_VERSION => {
+                println!("{}: up to date", map_path.display());
+            }
+            Ok(version) => {
+                println!("{}: migrated from v{} to v{}", map_path.display(), version, CURRENT_FORMAT_VERSION);
+            }
+            Err(error) => {
+                eprintln!("{}: {}", map_path.display(), error);
+                all_migrated = false;
+            }
         }
     }
+    all_migrated
 }
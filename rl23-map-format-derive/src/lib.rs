@@ -0,0 +1,287 @@
+//! Derives generating the editor inspectors of `rl23-map-format` entity components.
+//! The generated code only exists with the `editor-ui` feature of the crate using the derives.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input,
+    spanned::Spanned,
+    Data,
+    DeriveInput,
+    Expr,
+    Fields,
+    Ident,
+    LitStr
+};
+
+/// Implements `EntityComponentDataImpl` for a struct with named fields, drawing one
+/// `InspectorField` per field.
+///
+/// Struct attributes, all optional:
+/// `#[component(label = "...")]` names the component in the "+" menu, the struct name split into words by default;
+/// `#[component(applies_to(Logic, Unit))]` or `#[component(except(Loot))]` pick the `MapEntity` kinds
+/// the component can be added to, every kind by default.
+///
+/// Field attributes, all optional:
+/// `#[component(label = "...")]`, `#[component(range = 0..=16)]`, `#[component(speed = 0.5)]`,
+/// `#[component(pick_entity)]` for `EntityId` fields picked by clicking on the map
+/// and `#[component(skip)]` for fields left out of the inspector.
+#[proc_macro_derive(EntityComponent, attributes(component))]
+pub fn derive_entity_component(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match entity_component(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into()
+    }
+}
+
+/// Implements the "+" menu and the inspector of a component enum whose variants each wrap
+/// a single `EntityComponent`. Adding a variant is all it takes to register a component.
+#[proc_macro_derive(ComponentRegistry)]
+pub fn derive_component_registry(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match component_registry(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into()
+    }
+}
+
+enum Applicability {
+    All,
+    Only(Vec<Ident>),
+    Except(Vec<Ident>)
+}
+
+struct ComponentAttributes {
+    label: Option<LitStr>,
+    applicability: Applicability
+}
+
+#[derive(Default)]
+struct FieldAttributes {
+    label: Option<LitStr>,
+    range: Option<(Expr, Expr)>,
+    speed: Option<Expr>,
+    pick_entity: bool,
+    skip: bool
+}
+
+fn component_attributes(input: &DeriveInput) -> syn::Result<ComponentAttributes> {
+    let mut attributes = ComponentAttributes { label: None, applicability: Applicability::All };
+    for attribute in input.attrs.iter().filter(|attribute| attribute.path().is_ident("component")) {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("label") {
+                attributes.label = Some(meta.value()?.parse()?);
+                return Ok(());
+            }
+            let only = meta.path.is_ident("applies_to");
+            if only || meta.path.is_ident("except") {
+                let mut kinds = Vec::new();
+                meta.parse_nested_meta(|kind| {
+                    kinds.push(kind.path.require_ident()?.clone());
+                    Ok(())
+                })?;
+                attributes.applicability = if only {
+                    Applicability::Only(kinds)
+                } else {
+                    Applicability::Except(kinds)
+                };
+                return Ok(());
+            }
+            Err(meta.error("expected `label`, `applies_to` or `except`"))
+        })?;
+    }
+    Ok(attributes)
+}
+
+fn field_attributes(field: &syn::Field) -> syn::Result<FieldAttributes> {
+    let mut attributes = FieldAttributes::default();
+    for attribute in field.attrs.iter().filter(|attribute| attribute.path().is_ident("component")) {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("label") {
+                attributes.label = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("speed") {
+                attributes.speed = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("skip") {
+                attributes.skip = true;
+            } else if meta.path.is_ident("pick_entity") {
+                attributes.pick_entity = true;
+            } else if meta.path.is_ident("range") {
+                let range: Expr = meta.value()?.parse()?;
+                match range {
+                    Expr::Range(syn::ExprRange {
+                        start: Some(start),
+                        limits: syn::RangeLimits::Closed(_),
+                        end: Some(end),
+                        ..
+                    }) => {
+                        attributes.range = Some((*start, *end));
+                    }
+                    other => return Err(syn::Error::new(other.span(), "expected an inclusive range like `0..=16`"))
+                }
+            } else {
+                return Err(meta.error("expected `label`, `range`, `speed`, `pick_entity` or `skip`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(attributes)
+}
+
+/// `SpawnRandomUnit` becomes "Spawn Random Unit".
+fn words_of(name: &str) -> String {
+    let mut words = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if i > 0 && c.is_uppercase() {
+            words.push(' ');
+        }
+        words.push(c);
+    }
+    words
+}
+
+fn entity_component(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new(input.span(), "EntityComponent needs a struct with named fields"))
+        },
+        _ => return Err(syn::Error::new(input.span(), "EntityComponent can only be derived for structs"))
+    };
+
+    let attributes = component_attributes(input)?;
+    let label = attributes
+        .label
+        .map(|label| label.value())
+        .unwrap_or_else(|| words_of(&name.to_string()));
+
+    let applicable = match &attributes.applicability {
+        Applicability::All => quote!(true),
+        Applicability::Only(kinds) => quote! {
+            matches!(map_entity, #(::rl23_map_format::MapEntity::#kinds { .. })|*)
+        },
+        Applicability::Except(kinds) => quote! {
+            !matches!(map_entity, #(::rl23_map_format::MapEntity::#kinds { .. })|*)
+        }
+    };
+
+    let mut draw_fields = Vec::new();
+    for field in fields {
+        let field_attributes = field_attributes(field)?;
+        if field_attributes.skip {
+            continue;
+        }
+        let ident = field.ident.as_ref().unwrap();
+        let field_label = field_attributes
+            .label
+            .map(|label| label.value())
+            .unwrap_or_else(|| ident.to_string());
+        if field_attributes.pick_entity {
+            draw_fields.push(quote! {
+                ::rl23_map_format::pick_entity_field(&mut self.#ident, #field_label, ui);
+            });
+            continue;
+        }
+        let range = match field_attributes.range {
+            Some((start, end)) => quote!(Some((#start) as f64..=(#end) as f64)),
+            None => quote!(None)
+        };
+        let speed = match field_attributes.speed {
+            Some(speed) => quote!((#speed) as f64),
+            None => quote!(1.0)
+        };
+        draw_fields.push(quote! {
+            ::rl23_map_format::InspectorField::draw_field(
+                &mut self.#ident,
+                #field_label,
+                &::rl23_map_format::FieldHints { range: #range, speed: #speed },
+                id_generator,
+                ui
+            );
+        });
+    }
+
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        #[cfg(feature = "editor-ui")]
+        impl #impl_generics ::rl23_map_format::EntityComponentDataImpl for #name #type_generics #where_clause {
+            const LABEL: &'static str = #label;
+
+            #[allow(unused_variables)]
+            fn draw_egui(&mut self, id_generator: &mut ::rl23_map_format::IdGenerator, ui: &mut ::rl23_map_format::egui::Ui) {
+                ui.label(format!("{:<35}", Self::LABEL));
+                #(#draw_fields)*
+            }
+
+            #[allow(unused_variables)]
+            fn is_applicable_for_enitity_type(map_entity: ::rl23_map_format::MapEntity) -> bool {
+                #applicable
+            }
+
+            fn make_default(_id_generator: &mut ::rl23_map_format::IdGenerator) -> Self {
+                Default::default()
+            }
+        }
+    })
+}
+
+fn component_registry(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let variants = match &input.data {
+        Data::Enum(data) => &data.variants,
+        _ => return Err(syn::Error::new(input.span(), "ComponentRegistry can only be derived for enums"))
+    };
+
+    let mut menu_entries = Vec::new();
+    let mut draw_arms = Vec::new();
+    for variant in variants {
+        let variant_name = &variant.ident;
+        let component = match &variant.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0].ty,
+            _ => return Err(syn::Error::new(variant.span(), "every variant must wrap exactly one component"))
+        };
+        menu_entries.push(quote! {
+            if <#component as ::rl23_map_format::EntityComponentDataImpl>::is_applicable_for_enitity_type(map_entity)
+                && ui.button(<#component as ::rl23_map_format::EntityComponentDataImpl>::LABEL).clicked()
+            {
+                result = Some(Self::#variant_name(
+                    <#component as ::rl23_map_format::EntityComponentDataImpl>::make_default(id_generator)
+                ));
+            }
+        });
+        draw_arms.push(quote! {
+            Self::#variant_name(component) =>
+                ::rl23_map_format::EntityComponentDataImpl::draw_egui(component, id_generator, ui),
+        });
+    }
+
+    Ok(quote! {
+        #[cfg(feature = "editor-ui")]
+        impl #name {
+            /// Draws the "+" menu of components applicable to `map_entity`, returning the one picked.
+            pub fn draw_context_menu(
+                map_entity: ::rl23_map_format::MapEntity,
+                id_generator: &mut ::rl23_map_format::IdGenerator,
+                ui: &mut ::rl23_map_format::egui::Ui
+            ) -> Option<Self> {
+                let mut result = None;
+                ui.menu_button("+", |ui: &mut ::rl23_map_format::egui::Ui| {
+                    #(#menu_entries)*
+                });
+                result
+            }
+
+            /// Draws the inspector of the component, returns false when it gets deleted.
+            pub fn draw_egui(&mut self, id_generator: &mut ::rl23_map_format::IdGenerator, ui: &mut ::rl23_map_format::egui::Ui) -> bool {
+                match self {
+                    #(#draw_arms)*
+                }
+                let delete = ui.button("DELETE").clicked();
+                ui.separator();
+                !delete
+            }
+        }
+    })
+}
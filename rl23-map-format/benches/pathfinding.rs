@@ -0,0 +1,61 @@
+//! Pathfinding timings on a 256x256 map. Run with `cargo bench --bench pathfinding`.
+use std::time::{Duration, Instant};
+use rl23_map_format::{MapInfo, MovementProfile, PathOptions, TerrainKind, TilePos, WallKind};
+
+const SIZE: usize = 256;
+const RUNS: u32 = 20;
+
+/// Vertical walls every 8 columns with a gap alternating between the top and the bottom,
+/// so paths across the map have to snake, plus scattered cave water.
+fn serpentine_map() -> MapInfo {
+    let mut map = MapInfo::create_new(SIZE, SIZE, 0);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let pos = TilePos::new(x, y);
+            if x % 8 == 4 {
+                let gap_at_top = (x / 8) % 2 == 0;
+                let is_gap = if gap_at_top { y < 2 } else { y >= SIZE - 2 };
+                if !is_gap {
+                    map.wall_layer.set(pos, Some(WallKind::Bricks));
+                }
+            } else if (x * 7 + y * 13) % 11 == 0 {
+                map.terrain_layer.set(pos, TerrainKind::CaveWater);
+            }
+        }
+    }
+    map
+}
+
+fn bench(name: &str, mut f: impl FnMut()) {
+    f();
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        f();
+    }
+    let average: Duration = start.elapsed() / RUNS;
+    println!("{:<32} {:>10.3} ms", name, average.as_secs_f64() * 1000.0);
+}
+
+fn main() {
+    let map = serpentine_map();
+    let from = TilePos::new(0, 0);
+    let to = TilePos::new(SIZE - 1, SIZE - 1);
+    let corners = [from, TilePos::new(SIZE - 1, 0), TilePos::new(0, SIZE - 1), to];
+
+    bench("passability", || {
+        map.passability(MovementProfile::Walker);
+    });
+    for allow_diagonal in [false, true] {
+        let options = PathOptions::new(MovementProfile::Walker).with_diagonal(allow_diagonal);
+        let suffix = if allow_diagonal { "8-way" } else { "4-way" };
+        let costs = map.passability(options.profile);
+        assert!(rl23_map_format::find_path(&costs, from, to, allow_diagonal).is_some());
+
+        bench(&format!("a* corner to corner, {}", suffix), || {
+            rl23_map_format::find_path(&costs, from, to, allow_diagonal);
+        });
+        bench(&format!("distance map, 4 sources, {}", suffix), || {
+            rl23_map_format::DistanceMap::new(costs.clone(), &corners, allow_diagonal);
+        });
+    }
+}
@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use crate::{Grid, TilePos, WangEncoding, WangTerrain};
+
+pub const TILE_SIZE: usize = 32;
+
+/// Number of distinct tiles in a blob set once irrelevant corners are dropped.
+pub const BLOB_TILE_COUNT: usize = 47;
+
+/// Blob tiles are laid out in the atlas row by row, this many per row.
+pub const BLOB_ATLAS_COLUMNS: usize = 8;
+
+/// Normalized blob masks in ascending order. The position of a mask is its atlas cell index.
+const BLOB_MASKS: [u8; BLOB_TILE_COUNT] = [
+    0, 1, 4, 5, 7, 16, 17, 20, 21, 23, 28, 29, 31, 64, 65, 68,
+    69, 71, 80, 81, 84, 85, 87, 92, 93, 95, 112, 113, 116, 117, 119, 124,
+    125, 127, 193, 197, 199, 209, 213, 215, 221, 223, 241, 245, 247, 253, 255
+];
+
+const BLOB_NEIGHBOURS: [(u8, isize, isize); 8] = [
+    (BlobMask::NORTH, 0, -1),
+    (BlobMask::NORTH_EAST, 1, -1),
+    (BlobMask::EAST, 1, 0),
+    (BlobMask::SOUTH_EAST, 1, 1),
+    (BlobMask::SOUTH, 0, 1),
+    (BlobMask::SOUTH_WEST, -1, 1),
+    (BlobMask::WEST, -1, 0),
+    (BlobMask::NORTH_WEST, -1, -1)
+];
+
+/// How a terrain or wall kind is split into sprites.
+///
+/// `Wang16` is drawn on the dual grid: every sprite sits on a tile corner and is chosen
+/// by which of the four tiles around that corner belong to the kind.
+/// `Blob47` draws one sprite per tile, chosen by all eight neighbours, so isolated tiles
+/// and one tile wide corridors get sprites of their own.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Autotile {
+    Wang16 { x_offset: usize, y_offset: usize },
+    Blob47 { x_offset: usize, y_offset: usize }
+}
+
+impl From<WangTerrain> for Autotile {
+    fn from(wang: WangTerrain) -> Self {
+        Autotile::Wang16 { x_offset: wang.x_offset, y_offset: wang.y_offset }
+    }
+}
+
+impl Autotile {
+    /// Calls `draw` with the destination (in pixels, relative to the top left corner of the map)
+    /// and the atlas coordinates of every sprite needed to draw the tiles matching `is_member`.
+    pub fn for_each_sprite<T>(
+        self,
+        grid: &Grid<T>,
+        is_member: impl Fn(&T) -> bool,
+        mut draw: impl FnMut([isize; 2], [usize; 2])
+    ) {
+        if grid.bounds().is_empty() {
+            return;
+        }
+        let tile_size = TILE_SIZE as isize;
+        match self {
+            Autotile::Wang16 { x_offset, y_offset } => {
+                let wang = WangTerrain { x_offset, y_offset };
+                for j in 0..=grid.height() as isize {
+                    for i in 0..=grid.width() as isize {
+                        let encoding = WangEncoding::sample(grid, i, j, &is_member);
+                        if let Some(coords) = wang.get_final_coords(encoding) {
+                            draw([i * tile_size - tile_size / 2, j * tile_size - tile_size / 2], coords);
+                        }
+                    }
+                }
+            }
+            Autotile::Blob47 { .. } => {
+                for (pos, cell) in grid.iter() {
+                    if !is_member(cell) {
+                        continue;
+                    }
+                    if let Some(coords) = self.blob_coords(BlobMask::sample(grid, pos, &is_member)) {
+                        draw([pos.x as isize * tile_size, pos.y as isize * tile_size], coords);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Atlas coordinates of the sprite covering a whole tile, used to fill the terrain base.
+    pub fn full_coords(self) -> Option<[usize; 2]> {
+        match self {
+            Autotile::Wang16 { .. } => self.wang_coords(WangEncoding::from_bits(0b1111)),
+            Autotile::Blob47 { .. } => self.blob_coords(BlobMask(u8::MAX))
+        }
+    }
+
+    /// Atlas coordinates of the sprite for a dual grid corner. `None` when no tile around
+    /// the corner belongs to the kind, or when this is not a `Wang16` tiling.
+    pub fn wang_coords(self, encoding: WangEncoding) -> Option<[usize; 2]> {
+        match self {
+            Autotile::Wang16 { x_offset, y_offset } => {
+                WangTerrain { x_offset, y_offset }.get_final_coords(encoding)
+            }
+            Autotile::Blob47 { .. } => None
+        }
+    }
+
+    /// Atlas coordinates of the sprite for a tile with the given neighbours.
+    /// `None` when this is not a `Blob47` tiling.
+    pub fn blob_coords(self, mask: BlobMask) -> Option<[usize; 2]> {
+        match self {
+            Autotile::Blob47 { x_offset, y_offset } => {
+                let idx = mask.blob_index();
+                Some([
+                    x_offset + (idx % BLOB_ATLAS_COLUMNS) * TILE_SIZE,
+                    y_offset + (idx / BLOB_ATLAS_COLUMNS) * TILE_SIZE
+                ])
+            }
+            Autotile::Wang16 { .. } => None
+        }
+    }
+}
+
+impl WangEncoding {
+    pub const NORTH_EAST: u8 = 1;
+    pub const NORTH_WEST: u8 = 2;
+    pub const SOUTH_EAST: u8 = 4;
+    pub const SOUTH_WEST: u8 = 8;
+
+    pub fn from_bits(bits: u8) -> Self {
+        Self {
+            north_east: bits & Self::NORTH_EAST != 0,
+            north_west: bits & Self::NORTH_WEST != 0,
+            south_east: bits & Self::SOUTH_EAST != 0,
+            south_west: bits & Self::SOUTH_WEST != 0
+        }
+    }
+
+    pub fn bits(self) -> u8 {
+        let mut bits = 0;
+        if self.north_east { bits |= Self::NORTH_EAST; }
+        if self.north_west { bits |= Self::NORTH_WEST; }
+        if self.south_east { bits |= Self::SOUTH_EAST; }
+        if self.south_west { bits |= Self::SOUTH_WEST; }
+        bits
+    }
+
+    /// Encoding of the dual grid corner at the top left of tile `(i, j)`.
+    /// Tiles outside of the grid repeat the nearest edge tile.
+    pub fn sample<T>(grid: &Grid<T>, i: isize, j: isize, is_member: impl Fn(&T) -> bool) -> Self {
+        Self {
+            north_east: is_member(grid.get_clamped(i, j - 1)),
+            north_west: is_member(grid.get_clamped(i - 1, j - 1)),
+            south_east: is_member(grid.get_clamped(i, j)),
+            south_west: is_member(grid.get_clamped(i - 1, j))
+        }
+    }
+}
+
+/// Which of the eight neighbours of a tile belong to the same kind, one bit per direction.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BlobMask(pub u8);
+
+impl BlobMask {
+    pub const NORTH: u8 = 1;
+    pub const NORTH_EAST: u8 = 2;
+    pub const EAST: u8 = 4;
+    pub const SOUTH_EAST: u8 = 8;
+    pub const SOUTH: u8 = 16;
+    pub const SOUTH_WEST: u8 = 32;
+    pub const WEST: u8 = 64;
+    pub const NORTH_WEST: u8 = 128;
+
+    /// Neighbours of `pos` matching `is_member`. Tiles outside of the grid repeat the nearest edge tile.
+    pub fn sample<T>(grid: &Grid<T>, pos: TilePos, is_member: impl Fn(&T) -> bool) -> Self {
+        let (x, y) = (pos.x as isize, pos.y as isize);
+        let mut bits = 0;
+        for (bit, dx, dy) in BLOB_NEIGHBOURS {
+            if is_member(grid.get_clamped(x + dx, y + dy)) {
+                bits |= bit;
+            }
+        }
+        Self(bits)
+    }
+
+    /// Drops the corners whose two adjacent edges are not both set,
+    /// since such a corner does not change the shape of the tile.
+    pub fn normalized(self) -> Self {
+        let mut bits = self.0;
+        for (corner, first, second) in [
+            (Self::NORTH_EAST, Self::NORTH, Self::EAST),
+            (Self::SOUTH_EAST, Self::SOUTH, Self::EAST),
+            (Self::SOUTH_WEST, Self::SOUTH, Self::WEST),
+            (Self::NORTH_WEST, Self::NORTH, Self::WEST)
+        ] {
+            if bits & first == 0 || bits & second == 0 {
+                bits &= !corner;
+            }
+        }
+        Self(bits)
+    }
+
+    /// Atlas cell of the tile in a blob set, in `0..BLOB_TILE_COUNT`.
+    /// Cells are ordered by their normalized mask, so cell 0 is an isolated tile
+    /// and the last cell is a tile surrounded on all sides.
+    pub fn blob_index(self) -> usize {
+        BLOB_MASKS
+            .binary_search(&self.normalized().0)
+            .expect("every normalized mask is a blob tile")
+    }
+}
@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+use rl23_map_format::{MapInfo, SpawnPool, SpawnResolver, UnitDefinitions};
+
+const USAGE: &str = "usage: spawn-preview path_to_map.ron [--seed 1234] [--pool spawn_pool.ron] [--units units.ron]
+  prints what every spawner of the map may spawn and how likely it is,
+  then what it spawns with the seed (the seed of the map by default)";
+
+/// Prints `message` to stderr and exits with a failure status, so scripts notice.
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1)
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 || !args.len().is_multiple_of(2) {
+        fail(USAGE);
+    }
+    let map_path: PathBuf = (&args[1]).into();
+    let mut seed = None;
+    let mut pool = SpawnPool::default();
+    let mut units = UnitDefinitions::default();
+    for option in args[2..].chunks(2) {
+        let value = &option[1];
+        let result = match option[0].as_str() {
+            "--seed" => value.parse::<u64>().map(|value| seed = Some(value)).map_err(|error| error.to_string()),
+            "--pool" => SpawnPool::read_from_path(&value.into())
+                .map(|value| pool = value)
+                .map_err(|error| error.to_string()),
+            "--units" => UnitDefinitions::read_from_path(&value.into())
+                .map(|value| units = value)
+                .map_err(|error| error.to_string()),
+            _ => fail(USAGE)
+        };
+        if let Err(error) = result {
+            fail(&format!("bad {} {}: {}", option[0], value, error));
+        }
+    }
+
+    let map = match MapInfo::read_from_path(&map_path) {
+        Ok(map) => map,
+        Err(error) => fail(&format!("failed to open {}: {}", map_path.display(), error))
+    };
+    let seed = seed.unwrap_or(map.metadata.seed);
+    let resolver = SpawnResolver::new(&map, &pool, &units);
+    let spawns = resolver.resolve_all(seed);
+    if spawns.is_empty() {
+        println!("{} has no spawners", map_path.display());
+    }
+
+    for (spawner, choice) in spawns {
+        println!(
+            "entity {} at ({}, {}), levels {}..={}:",
+            spawner.id, spawner.position.x, spawner.position.y, spawner.spawn.min_level, spawner.spawn.max_level
+        );
+        let candidates = resolver.candidates(&spawner);
+        let total_weight: u64 = candidates.iter().map(|candidate| candidate.weight as u64).sum();
+        for candidate in candidates.iter() {
+            let members: Vec<String> = candidate.entry.members
+                .iter()
+                .map(|member| match (member.min_count, member.max_count) {
+                    (1, 1) => format!("{:?}", member.unit),
+                    (min, max) if min >= max => format!("{:?} x{}", member.unit, min),
+                    (min, max) => format!("{:?} x{}-{}", member.unit, min, max)
+                })
+                .collect();
+            println!(
+                "  {:>5.1}%  {} ({})",
+                candidate.weight as f64 * 100.0 / total_weight as f64,
+                candidate.entry.name,
+                members.join(", ")
+            );
+        }
+        match choice {
+            Some(choice) => {
+                let units: Vec<String> = choice.units.iter().map(|unit| format!("{:?}", unit)).collect();
+                println!("  seed {}: {} -> {}", seed, choice.entry, units.join(", "));
+            }
+            None => println!("  nothing can spawn here")
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::{
+    map_file::MapFile,
+    GatherableItem,
+    IdGenerator,
+    LightLevel,
+    MapFormatError,
+    MapMetadata,
+    PlacedEntity,
+    Region,
+    TerrainBlend,
+    TerrainKind,
+    WallKind,
+    CURRENT_FORMAT_VERSION
+};
+
+const MAGIC: &[u8; 8] = b"RL23MAP\0";
+const HEADER_SIZE: usize = MAGIC.len() + 4;
+/// Most tiles a binary map may have, so a few corrupt header bytes cannot ask for gigabytes.
+pub const MAX_BINARY_MAP_TILES: u64 = 1 << 24;
+
+/// Body of a binary map. Dense layers are run-length encoded as `(run length, value)` pairs,
+/// sparse layers are stored as `(tile index, value)` pairs sorted by tile index.
+/// Entities are stored in id order.
+#[derive(Deserialize, Serialize)]
+struct BinaryMap {
+    id_generator: IdGenerator,
+    width: u32,
+    height: u32,
+    terrain_runs: Vec<(u32, TerrainKind)>,
+    wall_runs: Vec<(u32, Option<WallKind>)>,
+    gatherable_layer: Vec<(u32, GatherableItem)>,
+    entities: Vec<PlacedEntity>,
+    terrain_blend: Option<TerrainBlend>,
+    ambient_light: LightLevel,
+    regions: Vec<Region>,
+    metadata: MapMetadata
+}
+
+fn encode_runs<T: Clone + PartialEq>(cells: &[T]) -> Vec<(u32, T)> {
+    let mut runs: Vec<(u32, T)> = Vec::new();
+    for cell in cells {
+        match runs.last_mut() {
+            Some((length, value)) if value == cell => *length += 1,
+            _ => runs.push((1, cell.clone()))
+        }
+    }
+    runs
+}
+
+/// Expands the runs of a layer, which must cover exactly `len` tiles.
+/// Run lengths are checked before anything is allocated, as they come straight from the file.
+fn decode_runs<T: Clone>(layer: &'static str, runs: Vec<(u32, T)>, len: u64) -> Result<Vec<T>, MapFormatError> {
+    let total = runs.iter().fold(0u64, |total, &(length, _)| total.saturating_add(length as u64));
+    if total != len {
+        return Err(MapFormatError::LayerSizeMismatch {
+            layer,
+            expected: usize::try_from(len).unwrap_or(usize::MAX),
+            actual: usize::try_from(total).unwrap_or(usize::MAX)
+        });
+    }
+    let mut cells = Vec::with_capacity(total as usize);
+    for (length, value) in runs {
+        cells.resize(cells.len() + length as usize, value);
+    }
+    Ok(cells)
+}
+
+/// Returns `None` if a tile index does not fit in a `u32`.
+fn encode_sparse<T: Clone>(cells: &HashMap<usize, T>) -> Option<Vec<(u32, T)>> {
+    let mut entries = cells
+        .iter()
+        .map(|(&idx, value)| Some((u32::try_from(idx).ok()?, value.clone())))
+        .collect::<Option<Vec<(u32, T)>>>()?;
+    entries.sort_unstable_by_key(|&(idx, _)| idx);
+    Some(entries)
+}
+
+fn decode_sparse<T>(entries: Vec<(u32, T)>) -> HashMap<usize, T> {
+    entries.into_iter().map(|(idx, value)| (idx as usize, value)).collect()
+}
+
+pub(crate) fn is_binary(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+pub(crate) fn to_bytes(file: &MapFile) -> Result<Vec<u8>, MapFormatError> {
+    let too_large = || MapFormatError::MapTooLarge {
+        width: file.width,
+        height: file.height
+    };
+    let tiles = (file.width as u64).checked_mul(file.height as u64);
+    if tiles.is_none_or(|tiles| tiles > MAX_BINARY_MAP_TILES) {
+        return Err(too_large());
+    }
+    let body = BinaryMap {
+        id_generator: file.id_generator.clone(),
+        width: u32::try_from(file.width).map_err(|_| too_large())?,
+        height: u32::try_from(file.height).map_err(|_| too_large())?,
+        terrain_runs: encode_runs(&file.terrain_layer),
+        wall_runs: encode_runs(&file.wall_layer),
+        gatherable_layer: encode_sparse(&file.gatherable_layer).ok_or_else(too_large)?,
+        entities: file.entities.clone(),
+        terrain_blend: file.terrain_blend.clone(),
+        ambient_light: file.ambient_light,
+        regions: file.regions.clone(),
+        metadata: file.metadata.clone()
+    };
+
+    let mut bytes = Vec::with_capacity(HEADER_SIZE);
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&CURRENT_FORMAT_VERSION.to_le_bytes());
+    bincode::serialize_into(&mut bytes, &body).map_err(MapFormatError::Binary)?;
+    Ok(bytes)
+}
+
+/// Binary maps are produced from RON sources, so only the current format version is accepted.
+/// Older binaries have to be converted again from their sources.
+pub(crate) fn from_bytes(bytes: &[u8]) -> Result<MapFile, MapFormatError> {
+    if bytes.len() < HEADER_SIZE || !is_binary(bytes) {
+        return Err(MapFormatError::InvalidBinaryHeader);
+    }
+    let mut version = [0u8; 4];
+    version.copy_from_slice(&bytes[MAGIC.len()..HEADER_SIZE]);
+    let version = u32::from_le_bytes(version);
+    if version < CURRENT_FORMAT_VERSION {
+        return Err(MapFormatError::OutdatedBinary {
+            found: version,
+            current: CURRENT_FORMAT_VERSION
+        });
+    }
+    if version > CURRENT_FORMAT_VERSION {
+        return Err(MapFormatError::UnsupportedVersion {
+            found: version,
+            supported: CURRENT_FORMAT_VERSION
+        });
+    }
+
+    let body: BinaryMap = bincode::deserialize(&bytes[HEADER_SIZE..])
+        .map_err(MapFormatError::Binary)?;
+    let len = body.width as u64 * body.height as u64;
+    if len > MAX_BINARY_MAP_TILES {
+        return Err(MapFormatError::MapTooLarge {
+            width: body.width as usize,
+            height: body.height as usize
+        });
+    }
+    Ok(MapFile {
+        format_version: version,
+        id_generator: body.id_generator,
+        width: body.width as usize,
+        height: body.height as usize,
+        terrain_layer: decode_runs("terrain_layer", body.terrain_runs, len)?,
+        gatherable_layer: decode_sparse(body.gatherable_layer),
+        entities: body.entities,
+        wall_layer: decode_runs("wall_layer", body.wall_runs, len)?,
+        terrain_blend: body.terrain_blend,
+        ambient_light: body.ambient_light,
+        regions: body.regions,
+        metadata: body.metadata
+    })
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use crate::{MapInfo, TerrainKind, Tileset, TILE_SIZE};
+
+/// Order in which terrain kinds are drawn over each other where they meet.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TerrainBlend {
+    /// Drawn under every tile. With `Mud`, mud tiles keep their own variant
+    /// and other tiles use the offset given here.
+    pub base: TerrainKind,
+    /// Kinds drawn over the base, from the bottom to the top.
+    pub priority: Vec<TerrainKind>
+}
+
+impl Default for TerrainBlend {
+    fn default() -> Self {
+        Self {
+            base: TerrainKind::Mud { offset: 10 },
+            priority: vec![
+                TerrainKind::Sand,
+                TerrainKind::Dirt,
+                TerrainKind::Tile,
+                TerrainKind::BrightTile,
+                TerrainKind::MossTile,
+                TerrainKind::VibrantTile,
+                TerrainKind::Grass,
+                TerrainKind::CaveWater,
+                TerrainKind::Water,
+                TerrainKind::Lava
+            ]
+        }
+    }
+}
+
+impl TerrainBlend {
+    /// Kinds to draw over the base, bottom first. Kinds missing from `priority` go under
+    /// the listed ones, so every kind on a map is drawn even with an incomplete list.
+    pub fn draw_order(&self) -> Vec<TerrainKind> {
+        let mut order: Vec<TerrainKind> = TerrainKind::WANG_KINDS
+            .into_iter()
+            .filter(|kind| !self.priority.contains(kind))
+            .collect();
+        for &kind in self.priority.iter() {
+            if !matches!(kind, TerrainKind::Mud { .. }) && !order.contains(&kind) {
+                order.push(kind);
+            }
+        }
+        order
+    }
+}
+
+impl MapInfo {
+    /// The blend order of this map, falling back to the one of the tileset.
+    pub fn terrain_blend<'a>(&'a self, tileset: &'a Tileset) -> &'a TerrainBlend {
+        self.terrain_blend.as_ref().unwrap_or(&tileset.terrain_blend)
+    }
+}
+
+impl Tileset {
+    /// Calls `draw` with the destination (in pixels, relative to the top left corner of the map)
+    /// and the atlas coordinates of every terrain sprite of `map`, in drawing order.
+    pub fn for_each_terrain_sprite(&self, map: &MapInfo, mut draw: impl FnMut([isize; 2], [usize; 2])) {
+        let blend = map.terrain_blend(self);
+        let tile_size = TILE_SIZE as isize;
+
+        let base_autotile = self.terrain(blend.base);
+        for (pos, &kind) in map.terrain_layer.iter() {
+            let coords = match (blend.base, kind) {
+                (TerrainKind::Mud { .. }, TerrainKind::Mud { offset }) => self.mud_coords(offset),
+                (TerrainKind::Mud { offset }, _) => self.mud_coords(offset),
+                _ => base_autotile.and_then(|autotile| autotile.full_coords())
+            };
+            if let Some(coords) = coords {
+                draw([pos.x as isize * tile_size, pos.y as isize * tile_size], coords);
+            }
+        }
+
+        for kind in blend.draw_order() {
+            if let Some(autotile) = self.terrain(kind) {
+                autotile.for_each_sprite(&map.terrain_layer, |&it| it == kind, &mut draw);
+            }
+        }
+    }
+}
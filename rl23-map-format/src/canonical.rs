@@ -0,0 +1,94 @@
+use std::{
+    collections::HashMap,
+    fmt::Write
+};
+use serde::Serialize;
+use crate::{map_file::MapFile, MapFormatError, PlacedEntity, Region};
+
+const INDENT: &str = "    ";
+
+fn value_str<T: Serialize>(value: &T) -> Result<String, MapFormatError> {
+    ron::to_string(value).map_err(MapFormatError::Serialize)
+}
+
+fn write_dense_layer<T: Serialize>(
+    out: &mut String,
+    name: &str,
+    width: usize,
+    cells: &[T]
+) -> Result<(), MapFormatError> {
+    writeln!(out, "{}{}: [", INDENT, name).unwrap();
+    for row in cells.chunks(width.max(1)) {
+        out.push_str(INDENT);
+        out.push_str(INDENT);
+        for cell in row {
+            out.push_str(&value_str(cell)?);
+            out.push(',');
+        }
+        out.push('\n');
+    }
+    writeln!(out, "{}],", INDENT).unwrap();
+    Ok(())
+}
+
+fn write_sparse_layer<T: Serialize>(
+    out: &mut String,
+    name: &str,
+    cells: &HashMap<usize, T>
+) -> Result<(), MapFormatError> {
+    let mut entries: Vec<_> = cells.iter().collect();
+    entries.sort_unstable_by_key(|&(&idx, _)| idx);
+
+    writeln!(out, "{}{}: {{", INDENT, name).unwrap();
+    for (idx, value) in entries {
+        writeln!(out, "{}{}{}: {},", INDENT, INDENT, idx, value_str(value)?).unwrap();
+    }
+    writeln!(out, "{}}},", INDENT).unwrap();
+    Ok(())
+}
+
+fn write_entities(out: &mut String, entities: &[PlacedEntity]) -> Result<(), MapFormatError> {
+    let mut entities: Vec<_> = entities.iter().collect();
+    entities.sort_unstable_by_key(|entity| entity.id());
+
+    writeln!(out, "{}entities: [", INDENT).unwrap();
+    for entity in entities {
+        writeln!(out, "{}{}{},", INDENT, INDENT, value_str(entity)?).unwrap();
+    }
+    writeln!(out, "{}],", INDENT).unwrap();
+    Ok(())
+}
+
+fn write_regions(out: &mut String, regions: &[Region]) -> Result<(), MapFormatError> {
+    let mut regions: Vec<_> = regions.iter().collect();
+    regions.sort_by_key(|region| region.id);
+
+    writeln!(out, "{}regions: [", INDENT).unwrap();
+    for region in regions {
+        writeln!(out, "{}{}{},", INDENT, INDENT, value_str(region)?).unwrap();
+    }
+    writeln!(out, "{}],", INDENT).unwrap();
+    Ok(())
+}
+
+/// Writes a map in a stable text form: sparse layers are sorted by tile index,
+/// and dense layers are written one map row per line.
+/// Equal maps always produce equal text, and editing a tile changes a single line.
+pub(crate) fn to_canonical_string(file: &MapFile) -> Result<String, MapFormatError> {
+    let mut out = String::new();
+    out.push_str("(\n");
+    writeln!(out, "{}format_version: {},", INDENT, file.format_version).unwrap();
+    writeln!(out, "{}id_generator: {},", INDENT, value_str(&file.id_generator)?).unwrap();
+    writeln!(out, "{}width: {},", INDENT, file.width).unwrap();
+    writeln!(out, "{}height: {},", INDENT, file.height).unwrap();
+    write_dense_layer(&mut out, "terrain_layer", file.width, &file.terrain_layer)?;
+    write_sparse_layer(&mut out, "gatherable_layer", &file.gatherable_layer)?;
+    write_entities(&mut out, &file.entities)?;
+    write_dense_layer(&mut out, "wall_layer", file.width, &file.wall_layer)?;
+    writeln!(out, "{}terrain_blend: {},", INDENT, value_str(&file.terrain_blend)?).unwrap();
+    writeln!(out, "{}ambient_light: {},", INDENT, file.ambient_light).unwrap();
+    write_regions(&mut out, &file.regions)?;
+    writeln!(out, "{}metadata: {},", INDENT, value_str(&file.metadata)?).unwrap();
+    out.push_str(")\n");
+    Ok(out)
+}
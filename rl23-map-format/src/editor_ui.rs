@@ -0,0 +1,186 @@
+use std::ops::RangeInclusive;
+use egui::Ui;
+use crate::{EntityId, IdGenerator, LootEntry, LootSource, MapEntity, TilePos, TriggerArea};
+
+/// Inspector of an entity component, usually implemented with `#[derive(EntityComponent)]`.
+pub trait EntityComponentDataImpl: Clone + Default {
+    /// Name of the component in the "+" menu and above its inspector.
+    const LABEL: &'static str;
+
+    fn is_applicable_for_enitity_type(map_entity: MapEntity) -> bool;
+    fn draw_egui(&mut self, id_generator: &mut IdGenerator, ui: &mut egui::Ui);
+    fn make_default(id_generator: &mut IdGenerator) -> Self;
+}
+
+/// Settings of a field given with `#[component(...)]` attributes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldHints {
+    pub range: Option<RangeInclusive<f64>>,
+    /// Change of a dragged value per pixel.
+    pub speed: f64
+}
+
+/// Field of a component which knows how to edit itself in an inspector.
+pub trait InspectorField {
+    fn draw_field(&mut self, label: &str, hints: &FieldHints, id_generator: &mut IdGenerator, ui: &mut Ui);
+}
+
+macro_rules! numeric_field(
+    ($($type_name:ty),*) => {
+        $(
+            impl InspectorField for $type_name {
+                fn draw_field(&mut self, label: &str, hints: &FieldHints, _id_generator: &mut IdGenerator, ui: &mut Ui) {
+                    let mut drag = egui::DragValue::new(self)
+                        .prefix(format!("{}: ", label))
+                        .speed(hints.speed);
+                    if let Some(range) = hints.range.clone() {
+                        drag = drag.clamp_range(range);
+                    }
+                    ui.add(drag);
+                }
+            }
+        )*
+    }
+);
+
+numeric_field!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64);
+
+impl InspectorField for bool {
+    fn draw_field(&mut self, label: &str, _hints: &FieldHints, _id_generator: &mut IdGenerator, ui: &mut Ui) {
+        ui.checkbox(self, label);
+    }
+}
+
+impl InspectorField for String {
+    fn draw_field(&mut self, label: &str, _hints: &FieldHints, _id_generator: &mut IdGenerator, ui: &mut Ui) {
+        ui.horizontal(|ui: &mut Ui| {
+            ui.label(format!("{}: ", label));
+            ui.text_edit_singleline(self);
+        });
+    }
+}
+
+impl InspectorField for TilePos {
+    fn draw_field(&mut self, label: &str, hints: &FieldHints, id_generator: &mut IdGenerator, ui: &mut Ui) {
+        ui.horizontal(|ui: &mut Ui| {
+            ui.label(format!("{}: ", label));
+            self.x.draw_field("x", hints, id_generator, ui);
+            self.y.draw_field("y", hints, id_generator, ui);
+        });
+    }
+}
+
+impl InspectorField for TriggerArea {
+    fn draw_field(&mut self, label: &str, hints: &FieldHints, id_generator: &mut IdGenerator, ui: &mut Ui) {
+        ui.horizontal(|ui: &mut Ui| {
+            ui.label(format!("{}: ", label));
+            if ui.radio(matches!(self, TriggerArea::Radius(_)), "radius").clicked() {
+                *self = TriggerArea::Radius(1);
+            }
+            if ui.radio(matches!(self, TriggerArea::Region(_)), "region").clicked() {
+                *self = TriggerArea::Region(0);
+            }
+        });
+        match self {
+            TriggerArea::Radius(radius) => radius.draw_field("radius", hints, id_generator, ui),
+            TriggerArea::Region(region) => region.draw_field("region", hints, id_generator, ui)
+        }
+    }
+}
+
+impl<T: InspectorField + Default> InspectorField for Vec<T> {
+    fn draw_field(&mut self, label: &str, hints: &FieldHints, id_generator: &mut IdGenerator, ui: &mut Ui) {
+        ui.label(format!("{}:", label));
+        let mut removed = None;
+        for (idx, item) in self.iter_mut().enumerate() {
+            ui.push_id(idx, |ui: &mut Ui| {
+                item.draw_field(&idx.to_string(), hints, id_generator, ui);
+                if ui.small_button("remove").clicked() {
+                    removed = Some(idx);
+                }
+            });
+        }
+        if let Some(idx) = removed {
+            self.remove(idx);
+        }
+        if ui.small_button(format!("add to {}", label)).clicked() {
+            self.push(T::default());
+        }
+    }
+}
+
+impl InspectorField for LootSource {
+    fn draw_field(&mut self, label: &str, _hints: &FieldHints, _id_generator: &mut IdGenerator, ui: &mut Ui) {
+        ui.horizontal(|ui: &mut Ui| {
+            ui.label(format!("{}: ", label));
+            let name = match self {
+                LootSource::Nothing => None,
+                LootSource::Item(name) | LootSource::Table(name) => Some(name.clone())
+            };
+            if ui.radio(matches!(self, LootSource::Nothing), "nothing").clicked() {
+                *self = LootSource::Nothing;
+            }
+            if ui.radio(matches!(self, LootSource::Item(_)), "item").clicked() {
+                *self = LootSource::Item(name.clone().unwrap_or_default());
+            }
+            if ui.radio(matches!(self, LootSource::Table(_)), "table").clicked() {
+                *self = LootSource::Table(name.unwrap_or_default());
+            }
+        });
+        if let LootSource::Item(name) | LootSource::Table(name) = self {
+            ui.text_edit_singleline(name);
+        }
+    }
+}
+
+impl InspectorField for LootEntry {
+    fn draw_field(&mut self, label: &str, hints: &FieldHints, id_generator: &mut IdGenerator, ui: &mut Ui) {
+        self.source.draw_field(label, hints, id_generator, ui);
+        ui.horizontal(|ui: &mut Ui| {
+            ui.add(egui::DragValue::new(&mut self.weight).prefix("weight: "));
+            ui.add(egui::DragValue::new(&mut self.min_quantity).prefix("quantity: "));
+            ui.add(egui::DragValue::new(&mut self.max_quantity).prefix("to: ").clamp_range(self.min_quantity..=u32::MAX));
+        });
+    }
+}
+
+/// Key in egui's memory of the field waiting for an entity to be clicked on the map.
+fn entity_pick_request() -> egui::Id {
+    egui::Id::new("rl23_entity_pick_request")
+}
+
+/// Edits an entity id, which can also be picked by clicking the entity on the map
+/// once the editor hands it over with `complete_entity_pick`.
+pub fn pick_entity_field(value: &mut EntityId, label: &str, ui: &mut Ui) {
+    ui.horizontal(|ui: &mut Ui| {
+        let field_id = ui.add(egui::DragValue::new(value).prefix(format!("{}: ", label))).id;
+        let picked = ui.data().get_temp::<EntityId>(field_id);
+        if let Some(picked) = picked {
+            *value = picked;
+            ui.data().remove::<EntityId>(field_id);
+        }
+        let waiting = ui.data().get_temp::<egui::Id>(entity_pick_request()) == Some(field_id);
+        if waiting {
+            ui.label("click an entity");
+            if ui.button("cancel").clicked() {
+                ui.data().remove::<egui::Id>(entity_pick_request());
+            }
+        } else if ui.button("pick").clicked() {
+            ui.data().insert_temp(entity_pick_request(), field_id);
+        }
+    });
+}
+
+/// Whether an inspector waits for an entity to be clicked on the map.
+pub fn entity_pick_pending(ctx: &egui::Context) -> bool {
+    ctx.data().get_temp::<egui::Id>(entity_pick_request()).is_some()
+}
+
+/// Hands the clicked entity over to the waiting inspector field.
+pub fn complete_entity_pick(ctx: &egui::Context, id: EntityId) {
+    let mut data = ctx.data();
+    if let Some(field_id) = data.get_temp::<egui::Id>(entity_pick_request()) {
+        data.insert_temp(field_id, id);
+        data.remove::<egui::Id>(entity_pick_request());
+    }
+}
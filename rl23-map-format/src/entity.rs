@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use crate::{EntityComponentData, GridBounds, MapEntity, TilePos};
+
+/// Stable identifier of an entity, produced by the map's `IdGenerator`.
+pub type EntityId = u64;
+
+/// Entity placed on a map. Its id and position are changed through `EntityTable`
+/// only, so the position index always stays in sync.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct PlacedEntity {
+    id: EntityId,
+    position: TilePos,
+    pub entity: MapEntity,
+    pub components: Vec<EntityComponentData>
+}
+
+impl PlacedEntity {
+    pub fn new(id: EntityId, position: TilePos, entity: MapEntity) -> Self {
+        Self {
+            id,
+            position,
+            entity,
+            components: Vec::new()
+        }
+    }
+
+    pub fn id(&self) -> EntityId {
+        self.id
+    }
+
+    pub fn position(&self) -> TilePos {
+        self.position
+    }
+}
+
+/// Entities of a map keyed by id. A tile may hold any number of entities.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EntityTable {
+    bounds: GridBounds,
+    entities: BTreeMap<EntityId, PlacedEntity>,
+    by_position: BTreeMap<TilePos, Vec<EntityId>>
+}
+
+impl EntityTable {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            bounds: GridBounds::new(width, height),
+            entities: BTreeMap::new(),
+            by_position: BTreeMap::new()
+        }
+    }
+
+    pub fn bounds(&self) -> GridBounds {
+        self.bounds
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    pub fn contains(&self, id: EntityId) -> bool {
+        self.entities.contains_key(&id)
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&PlacedEntity> {
+        self.entities.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: EntityId) -> Option<&mut PlacedEntity> {
+        self.entities.get_mut(&id)
+    }
+
+    /// Adds `entity`, replacing the entity with the same id.
+    /// Returns `false` and drops `entity` if its position is out of bounds.
+    pub fn insert(&mut self, entity: PlacedEntity) -> bool {
+        if !self.bounds.contains(entity.position) {
+            return false;
+        }
+        self.remove(entity.id);
+        self.index(entity.id, entity.position);
+        self.entities.insert(entity.id, entity);
+        true
+    }
+
+    pub fn remove(&mut self, id: EntityId) -> Option<PlacedEntity> {
+        let entity = self.entities.remove(&id)?;
+        self.unindex(id, entity.position);
+        Some(entity)
+    }
+
+    /// Moves an entity keeping its id and components.
+    /// Returns `false` if there is no such entity or `position` is out of bounds.
+    pub fn move_to(&mut self, id: EntityId, position: TilePos) -> bool {
+        if !self.bounds.contains(position) {
+            return false;
+        }
+        let old_position = match self.entities.get_mut(&id) {
+            Some(entity) => std::mem::replace(&mut entity.position, position),
+            None => return false
+        };
+        self.unindex(id, old_position);
+        self.index(id, position);
+        true
+    }
+
+    /// Ids of the entities at `position`, in ascending order.
+    pub fn ids_at(&self, position: TilePos) -> &[EntityId] {
+        self.by_position.get(&position).map_or(&[], |ids| ids.as_slice())
+    }
+
+    pub fn at(&self, position: TilePos) -> impl Iterator<Item = &PlacedEntity> {
+        self.ids_at(position).iter().map(move |id| &self.entities[id])
+    }
+
+    pub fn is_occupied(&self, position: TilePos) -> bool {
+        self.by_position.contains_key(&position)
+    }
+
+    /// Entities in ascending id order.
+    pub fn iter(&self) -> impl Iterator<Item = &PlacedEntity> {
+        self.entities.values()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut PlacedEntity> {
+        self.entities.values_mut()
+    }
+
+    /// Occupied positions in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = TilePos> + '_ {
+        self.by_position.keys().copied()
+    }
+
+    pub fn retain(&mut self, mut f: impl FnMut(&PlacedEntity) -> bool) {
+        let removed: Vec<EntityId> = self.entities
+            .values()
+            .filter(|entity| !f(entity))
+            .map(|entity| entity.id)
+            .collect();
+        for id in removed {
+            self.remove(id);
+        }
+    }
+
+    /// Changes the bounds and shifts every entity by `offset`.
+    /// Entities which end up outside of the new bounds are removed and returned
+    /// with their old positions.
+    pub(crate) fn relocate(&mut self, width: usize, height: usize, offset: (isize, isize)) -> Vec<PlacedEntity> {
+        let old_table = std::mem::replace(self, EntityTable::new(width, height));
+        let mut dropped = Vec::new();
+        for (_, mut entity) in old_table.entities {
+            match entity.position.offset(offset.0, offset.1) {
+                Some(new_position) if self.bounds.contains(new_position) => {
+                    entity.position = new_position;
+                    self.insert(entity);
+                }
+                _ => dropped.push(entity)
+            }
+        }
+        dropped
+    }
+
+    fn index(&mut self, id: EntityId, position: TilePos) {
+        let ids = self.by_position.entry(position).or_default();
+        if let Err(idx) = ids.binary_search(&id) {
+            ids.insert(idx, id);
+        }
+    }
+
+    fn unindex(&mut self, id: EntityId, position: TilePos) {
+        if let Some(ids) = self.by_position.get_mut(&position) {
+            ids.retain(|&it| it != id);
+            if ids.is_empty() {
+                self.by_position.remove(&position);
+            }
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter}
+};
+use crate::{EntityId, RegionId, TilePos};
+
+#[derive(Debug)]
+pub enum MapFormatError {
+    Io(std::io::Error),
+    Parse {
+        line: usize,
+        column: usize,
+        error: ron::Error
+    },
+    Serialize(ron::Error),
+    Binary(bincode::Error),
+    InvalidBinaryHeader,
+    UnsupportedVersion {
+        found: u32,
+        supported: u32
+    },
+    OutdatedBinary {
+        found: u32,
+        current: u32
+    },
+    MapTooLarge {
+        width: usize,
+        height: usize
+    },
+    EmptyMap {
+        width: usize,
+        height: usize
+    },
+    LayerSizeMismatch {
+        layer: &'static str,
+        expected: usize,
+        actual: usize
+    },
+    KeyOutOfBounds {
+        layer: &'static str,
+        idx: usize
+    },
+    EntityOutOfBounds {
+        id: EntityId,
+        position: TilePos
+    },
+    DuplicateEntityId {
+        id: EntityId
+    },
+    DuplicateRegionId {
+        id: RegionId
+    },
+    UnknownLootTable {
+        name: String
+    },
+    LootTableTooDeep {
+        name: String
+    },
+    LootQuantityTooLarge {
+        name: String,
+        quantity: u32
+    },
+    LootTableCycle {
+        /// Names of the tables in the cycle, starting and ending with the same table.
+        names: Vec<String>
+    }
+}
+
+impl Display for MapFormatError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapFormatError::Io(error) => write!(f, "io error: {}", error),
+            MapFormatError::Parse { line, column, error } => {
+                write!(f, "parse error at {}:{}: {}", line, column, error)
+            }
+            MapFormatError::Serialize(error) => write!(f, "serialization error: {}", error),
+            MapFormatError::Binary(error) => write!(f, "binary map error: {}", error),
+            MapFormatError::InvalidBinaryHeader => write!(f, "not a binary map file"),
+            MapFormatError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "format version {} is newer than the supported version {}",
+                found, supported
+            ),
+            MapFormatError::OutdatedBinary { found, current } => write!(
+                f,
+                "binary map version {} is older than the current version {}, convert it again from its RON source",
+                found, current
+            ),
+            MapFormatError::MapTooLarge { width, height } => write!(
+                f,
+                "map of {}x{} tiles is too large",
+                width, height
+            ),
+            MapFormatError::EmptyMap { width, height } => {
+                write!(f, "map of {}x{} tiles has no tiles, both sides must be at least 1", width, height)
+            }
+            MapFormatError::LayerSizeMismatch { layer, expected, actual } => write!(
+                f,
+                "{} has {} entries, but width * height is {}",
+                layer, actual, expected
+            ),
+            MapFormatError::KeyOutOfBounds { layer, idx } => write!(
+                f,
+                "{} has an entry at {} which is outside of the map",
+                layer, idx
+            ),
+            MapFormatError::EntityOutOfBounds { id, position } => write!(
+                f,
+                "entity {} at ({}, {}) is outside of the map",
+                id, position.x, position.y
+            ),
+            MapFormatError::DuplicateEntityId { id } => {
+                write!(f, "entity id {} is used more than once", id)
+            }
+            MapFormatError::DuplicateRegionId { id } => {
+                write!(f, "region id {} is used more than once", id)
+            }
+            MapFormatError::UnknownLootTable { name } => {
+                write!(f, "there is no loot table named {}", name)
+            }
+            MapFormatError::LootTableTooDeep { name } => write!(
+                f,
+                "loot table {} is nested more than {} tables deep, it probably refers to itself",
+                name, crate::MAX_LOOT_DEPTH
+            ),
+            MapFormatError::LootQuantityTooLarge { name, quantity } => write!(
+                f,
+                "loot table {} is rolled up to {} times by one entry, at most {} are allowed",
+                name, quantity, crate::MAX_NESTED_LOOT_QUANTITY
+            ),
+            MapFormatError::LootTableCycle { names } => {
+                write!(f, "loot tables refer to each other in a cycle: {}", names.join(" -> "))
+            }
+        }
+    }
+}
+
+impl Error for MapFormatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MapFormatError::Io(error) => Some(error),
+            MapFormatError::Parse { error, .. } => Some(error),
+            MapFormatError::Serialize(error) => Some(error),
+            MapFormatError::Binary(error) => Some(error),
+            MapFormatError::InvalidBinaryHeader |
+            MapFormatError::UnsupportedVersion { .. } |
+            MapFormatError::OutdatedBinary { .. } |
+            MapFormatError::MapTooLarge { .. } |
+            MapFormatError::EmptyMap { .. } |
+            MapFormatError::LayerSizeMismatch { .. } |
+            MapFormatError::KeyOutOfBounds { .. } |
+            MapFormatError::EntityOutOfBounds { .. } |
+            MapFormatError::DuplicateEntityId { .. } |
+            MapFormatError::DuplicateRegionId { .. } |
+            MapFormatError::UnknownLootTable { .. } |
+            MapFormatError::LootTableTooDeep { .. } |
+            MapFormatError::LootQuantityTooLarge { .. } |
+            MapFormatError::LootTableCycle { .. } => None
+        }
+    }
+}
+
+impl From<std::io::Error> for MapFormatError {
+    fn from(error: std::io::Error) -> Self {
+        MapFormatError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for MapFormatError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        MapFormatError::Parse {
+            line: error.position.line,
+            column: error.position.col,
+            error: error.code
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use std::fmt::{Display, Formatter};
+use crate::{
+    Decor,
+    EntityId,
+    MapEntity,
+    MapInfo,
+    PlacedEntity,
+    TilePos,
+    Tree,
+    Unit
+};
+
+/// One tile covered by an entity, relative to the tile the entity is placed on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FootprintCell {
+    pub dx: isize,
+    pub dy: isize,
+    /// Two entities overlap when both have a solid cell on the same tile.
+    pub solid: bool,
+    pub blocks_movement: bool
+}
+
+impl FootprintCell {
+    const fn new(dx: isize, dy: isize, solid: bool, blocks_movement: bool) -> Self {
+        Self { dx, dy, solid, blocks_movement }
+    }
+}
+
+/// Footprint cells never reach further than this many tiles from the entity position.
+pub const MAX_FOOTPRINT_REACH: usize = 1;
+
+const MARKER: &[FootprintCell] = &[FootprintCell::new(0, 0, false, false)];
+const PASSABLE: &[FootprintCell] = &[FootprintCell::new(0, 0, true, false)];
+const BLOCKING: &[FootprintCell] = &[FootprintCell::new(0, 0, true, true)];
+const BLOCKING_TO_THE_RIGHT: &[FootprintCell] = &[
+    FootprintCell::new(0, 0, true, true),
+    FootprintCell::new(1, 0, true, true)
+];
+const BLOCKING_TO_THE_LEFT: &[FootprintCell] = &[
+    FootprintCell::new(-1, 0, true, true),
+    FootprintCell::new(0, 0, true, true)
+];
+/// Wide trees block only at the trunk, their branches can be walked under.
+const WIDE_TREE: &[FootprintCell] = &[
+    FootprintCell::new(-1, 0, true, false),
+    FootprintCell::new(0, 0, true, true),
+    FootprintCell::new(1, 0, true, false)
+];
+
+impl MapEntity {
+    /// Tiles covered by the entity. Every footprint contains the `(0, 0)` cell.
+    pub fn footprint(self) -> &'static [FootprintCell] {
+        match self {
+            MapEntity::Door => PASSABLE,
+            MapEntity::ClosedDoor(_) => BLOCKING,
+            MapEntity::Decor(decor) => match decor {
+                Decor::Bed1GreenLeft | Decor::Bed2GreenLeft |
+                Decor::Bed1BlueLeft | Decor::Bed2BlueLeft |
+                Decor::Bed1WhiteLeft | Decor::Bed2WhiteLeft |
+                Decor::TableGreen | Decor::TableBlue | Decor::TableBlack |
+                Decor::OvenLeft => BLOCKING_TO_THE_RIGHT,
+                Decor::Bed1GreenRight | Decor::Bed2GreenRight |
+                Decor::Bed1BlueRight | Decor::Bed2BlueRight |
+                Decor::Bed1WhiteRight | Decor::Bed2WhiteRight |
+                Decor::OvenRight => BLOCKING_TO_THE_LEFT,
+                Decor::Closet | Decor::Dresser1 | Decor::Dresser2 => BLOCKING
+            },
+            MapEntity::Unit(Unit::Gorynich) => BLOCKING_TO_THE_LEFT,
+            MapEntity::Unit(_) => BLOCKING,
+            MapEntity::Tree(tree) => match tree {
+                Tree::Pine1 | Tree::Oak | Tree::Pine1Cursed | Tree::OakCursed => WIDE_TREE,
+                Tree::Pine2 | Tree::Birch | Tree::Pine2Cursed | Tree::BirchCursed => BLOCKING
+            },
+            MapEntity::Loot | MapEntity::Logic => MARKER
+        }
+    }
+}
+
+/// Reason an entity can not be placed on a tile.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PlacementConflict {
+    /// A footprint cell would lie outside of the map.
+    OutOfBounds,
+    /// A solid footprint cell would share `position` with a solid cell of entity `id`.
+    Overlaps { id: EntityId, position: TilePos }
+}
+
+impl Display for PlacementConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlacementConflict::OutOfBounds => write!(f, "footprint does not fit into the map"),
+            PlacementConflict::Overlaps { id, position } => write!(
+                f,
+                "overlaps entity {} at ({}, {})",
+                id, position.x, position.y
+            )
+        }
+    }
+}
+
+impl MapInfo {
+    /// Tiles covered by `entity` placed at `position`, skipping cells outside of the map.
+    pub fn footprint_tiles(
+        &self,
+        entity: MapEntity,
+        position: TilePos
+    ) -> impl Iterator<Item = (TilePos, FootprintCell)> + '_ {
+        let bounds = self.bounds();
+        entity.footprint().iter().filter_map(move |&cell| {
+            position
+                .offset(cell.dx, cell.dy)
+                .filter(|&pos| bounds.contains(pos))
+                .map(|pos| (pos, cell))
+        })
+    }
+
+    /// Entities covering `position` together with the footprint cell which covers it, in id order.
+    pub fn occupants(&self, position: TilePos) -> Vec<(&PlacedEntity, FootprintCell)> {
+        let min = TilePos::new(
+            position.x.saturating_sub(MAX_FOOTPRINT_REACH),
+            position.y.saturating_sub(MAX_FOOTPRINT_REACH)
+        );
+        let side = MAX_FOOTPRINT_REACH * 2 + 1;
+
+        let mut occupants = Vec::new();
+        for origin in self.bounds().rect(min, side, side) {
+            for placed in self.entities.at(origin) {
+                let covering_cell = placed.entity.footprint().iter().find(|cell| {
+                    origin.offset(cell.dx, cell.dy) == Some(position)
+                });
+                if let Some(&cell) = covering_cell {
+                    occupants.push((placed, cell));
+                }
+            }
+        }
+        occupants.sort_unstable_by_key(|(placed, _)| placed.id());
+        occupants
+    }
+
+    /// Whether an entity with a movement blocking cell covers `position`.
+    pub fn is_blocked_by_entity(&self, position: TilePos) -> bool {
+        self.occupants(position).iter().any(|(_, cell)| cell.blocks_movement)
+    }
+
+    /// Everything preventing `entity` from being placed at `position`. Each overlapped entity
+    /// is listed once, at the first tile they share. The entity with id `ignore` is skipped,
+    /// so an entity can be checked against its own old place.
+    pub fn placement_conflicts(
+        &self,
+        entity: MapEntity,
+        position: TilePos,
+        ignore: Option<EntityId>
+    ) -> Vec<PlacementConflict> {
+        let mut conflicts = Vec::new();
+        for cell in entity.footprint() {
+            let pos = match position.offset(cell.dx, cell.dy) {
+                Some(pos) if self.bounds().contains(pos) => pos,
+                _ => {
+                    if !conflicts.contains(&PlacementConflict::OutOfBounds) {
+                        conflicts.push(PlacementConflict::OutOfBounds);
+                    }
+                    continue;
+                }
+            };
+            if !cell.solid {
+                continue;
+            }
+            for (other, other_cell) in self.occupants(pos) {
+                let already_listed = conflicts.iter().any(|conflict| {
+                    matches!(conflict, PlacementConflict::Overlaps { id, .. } if *id == other.id())
+                });
+                if other_cell.solid && Some(other.id()) != ignore && !already_listed {
+                    conflicts.push(PlacementConflict::Overlaps { id: other.id(), position: pos });
+                }
+            }
+        }
+        conflicts
+    }
+}
@@ -0,0 +1,352 @@
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap
+};
+use serde::{Deserialize, Serialize};
+
+/// Position of a tile on a map. Positions are ordered row by row,
+/// the same way tiles are laid out in dense layers.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct TilePos {
+    pub x: usize,
+    pub y: usize
+}
+
+impl TilePos {
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+
+    /// Returns `None` if the offset position would have a negative coordinate.
+    pub fn offset(self, dx: isize, dy: isize) -> Option<Self> {
+        Some(Self {
+            x: self.x.checked_add_signed(dx)?,
+            y: self.y.checked_add_signed(dy)?
+        })
+    }
+}
+
+impl PartialOrd for TilePos {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TilePos {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.y, self.x).cmp(&(other.y, other.x))
+    }
+}
+
+const NEIGHBOUR_OFFSETS_4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+const NEIGHBOUR_OFFSETS_8: [(isize, isize); 8] = [
+    (0, -1), (1, -1), (1, 0), (1, 1),
+    (0, 1), (-1, 1), (-1, 0), (-1, -1)
+];
+
+/// Width and height shared by `Grid` and `SparseGrid`. All index math lives here.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct GridBounds {
+    pub width: usize,
+    pub height: usize
+}
+
+impl GridBounds {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height }
+    }
+
+    /// Number of tiles, saturating at `usize::MAX` for bounds no grid could hold.
+    pub fn len(self) -> usize {
+        self.width.saturating_mul(self.height)
+    }
+
+    /// Number of tiles, `None` if it does not fit in a `usize`.
+    pub fn checked_len(self) -> Option<usize> {
+        self.width.checked_mul(self.height)
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(self, pos: TilePos) -> bool {
+        pos.x < self.width && pos.y < self.height
+    }
+
+    pub fn index_of(self, pos: TilePos) -> Option<usize> {
+        if self.contains(pos) {
+            Some(pos.y * self.width + pos.x)
+        } else {
+            None
+        }
+    }
+
+    pub fn pos_of(self, idx: usize) -> Option<TilePos> {
+        if idx < self.len() {
+            Some(TilePos::new(idx % self.width, idx / self.width))
+        } else {
+            None
+        }
+    }
+
+    /// Clamps possibly out of bounds coordinates to the nearest edge tile.
+    /// Must not be called on empty bounds.
+    pub fn clamp(self, x: isize, y: isize) -> TilePos {
+        TilePos::new(
+            x.clamp(0, self.width as isize - 1) as usize,
+            y.clamp(0, self.height as isize - 1) as usize
+        )
+    }
+
+    /// All positions in row-major order.
+    pub fn positions(self) -> impl Iterator<Item = TilePos> {
+        (0..self.height).flat_map(move |y| (0..self.width).map(move |x| TilePos::new(x, y)))
+    }
+
+    /// Orthogonal neighbours of `pos` which lie inside the bounds.
+    pub fn neighbours4(self, pos: TilePos) -> impl Iterator<Item = TilePos> {
+        self.neighbours_by(pos, &NEIGHBOUR_OFFSETS_4)
+    }
+
+    /// Orthogonal and diagonal neighbours of `pos` which lie inside the bounds.
+    pub fn neighbours8(self, pos: TilePos) -> impl Iterator<Item = TilePos> {
+        self.neighbours_by(pos, &NEIGHBOUR_OFFSETS_8)
+    }
+
+    fn neighbours_by(
+        self,
+        pos: TilePos,
+        offsets: &'static [(isize, isize)]
+    ) -> impl Iterator<Item = TilePos> {
+        offsets
+            .iter()
+            .filter_map(move |&(dx, dy)| pos.offset(dx, dy))
+            .filter(move |&neighbour| self.contains(neighbour))
+    }
+
+    /// Positions of the `width` x `height` rectangle starting at `min`, clipped to the bounds.
+    pub fn rect(self, min: TilePos, width: usize, height: usize) -> impl Iterator<Item = TilePos> {
+        let max_x = min.x.saturating_add(width).min(self.width);
+        let max_y = min.y.saturating_add(height).min(self.height);
+        (min.y..max_y).flat_map(move |y| (min.x..max_x).map(move |x| TilePos::new(x, y)))
+    }
+}
+
+/// Dense layer holding one value per tile.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid<T> {
+    bounds: GridBounds,
+    cells: Vec<T>
+}
+
+impl<T> Grid<T> {
+    /// Panics if `width * height` overflows.
+    pub fn new(width: usize, height: usize, fill: T) -> Self where T: Clone {
+        let bounds = GridBounds::new(width, height);
+        let len = bounds.checked_len().expect("grid size overflows usize");
+        Self {
+            bounds,
+            cells: vec![fill; len]
+        }
+    }
+
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(TilePos) -> T) -> Self {
+        let bounds = GridBounds::new(width, height);
+        Self {
+            bounds,
+            cells: bounds.positions().map(&mut f).collect()
+        }
+    }
+
+    /// Wraps row-major `cells`. Returns them back if their count is not `width * height`,
+    /// which includes sizes overflowing `usize`.
+    pub fn from_vec(width: usize, height: usize, cells: Vec<T>) -> Result<Self, Vec<T>> {
+        let bounds = GridBounds::new(width, height);
+        if bounds.checked_len() == Some(cells.len()) {
+            Ok(Self { bounds, cells })
+        } else {
+            Err(cells)
+        }
+    }
+
+    pub fn bounds(&self) -> GridBounds {
+        self.bounds
+    }
+
+    pub fn width(&self) -> usize {
+        self.bounds.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.bounds.height
+    }
+
+    pub fn contains(&self, pos: TilePos) -> bool {
+        self.bounds.contains(pos)
+    }
+
+    pub fn get(&self, pos: TilePos) -> Option<&T> {
+        self.bounds.index_of(pos).map(|idx| &self.cells[idx])
+    }
+
+    pub fn get_mut(&mut self, pos: TilePos) -> Option<&mut T> {
+        self.bounds.index_of(pos).map(move |idx| &mut self.cells[idx])
+    }
+
+    /// Returns `false` and drops `value` if `pos` is out of bounds.
+    pub fn set(&mut self, pos: TilePos, value: T) -> bool {
+        match self.get_mut(pos) {
+            Some(cell) => {
+                *cell = value;
+                true
+            }
+            None => false
+        }
+    }
+
+    /// Samples the grid at possibly out of bounds coordinates, repeating the edge tiles.
+    /// Must not be called on an empty grid.
+    pub fn get_clamped(&self, x: isize, y: isize) -> &T {
+        let idx = self.bounds.index_of(self.bounds.clamp(x, y)).unwrap();
+        &self.cells[idx]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (TilePos, &T)> {
+        self.bounds.positions().zip(self.cells.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (TilePos, &mut T)> {
+        self.bounds.positions().zip(self.cells.iter_mut())
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = TilePos> {
+        self.bounds.positions()
+    }
+
+    pub fn neighbours4(&self, pos: TilePos) -> impl Iterator<Item = TilePos> {
+        self.bounds.neighbours4(pos)
+    }
+
+    pub fn neighbours8(&self, pos: TilePos) -> impl Iterator<Item = TilePos> {
+        self.bounds.neighbours8(pos)
+    }
+
+    pub fn rect(&self, min: TilePos, width: usize, height: usize) -> impl Iterator<Item = TilePos> {
+        self.bounds.rect(min, width, height)
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.cells
+    }
+}
+
+/// Layer holding values for some of the tiles. Iteration goes in row-major order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SparseGrid<T> {
+    bounds: GridBounds,
+    cells: BTreeMap<TilePos, T>
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            bounds: GridBounds::new(width, height),
+            cells: BTreeMap::new()
+        }
+    }
+
+    pub fn bounds(&self) -> GridBounds {
+        self.bounds
+    }
+
+    pub fn width(&self) -> usize {
+        self.bounds.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.bounds.height
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, pos: TilePos) -> bool {
+        self.cells.contains_key(&pos)
+    }
+
+    pub fn get(&self, pos: TilePos) -> Option<&T> {
+        self.cells.get(&pos)
+    }
+
+    pub fn get_mut(&mut self, pos: TilePos) -> Option<&mut T> {
+        self.cells.get_mut(&pos)
+    }
+
+    /// Returns `false` and drops `value` if `pos` is out of bounds.
+    pub fn insert(&mut self, pos: TilePos, value: T) -> bool {
+        if self.bounds.contains(pos) {
+            self.cells.insert(pos, value);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn remove(&mut self, pos: TilePos) -> Option<T> {
+        self.cells.remove(&pos)
+    }
+
+    pub fn retain(&mut self, mut f: impl FnMut(TilePos, &mut T) -> bool) {
+        self.cells.retain(|&pos, value| f(pos, value))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (TilePos, &T)> {
+        self.cells.iter().map(|(&pos, value)| (pos, value))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (TilePos, &mut T)> {
+        self.cells.iter_mut().map(|(&pos, value)| (pos, value))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = TilePos> + '_ {
+        self.cells.keys().copied()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.values()
+    }
+
+    pub fn neighbours4(&self, pos: TilePos) -> impl Iterator<Item = TilePos> {
+        self.bounds.neighbours4(pos)
+    }
+
+    pub fn neighbours8(&self, pos: TilePos) -> impl Iterator<Item = TilePos> {
+        self.bounds.neighbours8(pos)
+    }
+
+    /// Occupied positions inside the `width` x `height` rectangle starting at `min`.
+    pub fn rect(&self, min: TilePos, width: usize, height: usize) -> impl Iterator<Item = (TilePos, &T)> {
+        self.bounds
+            .rect(min, width, height)
+            .filter_map(move |pos| self.cells.get(&pos).map(|value| (pos, value)))
+    }
+}
+
+impl<T> IntoIterator for SparseGrid<T> {
+    type Item = (TilePos, T);
+    type IntoIter = std::collections::btree_map::IntoIter<TilePos, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.cells.into_iter()
+    }
+}
@@ -1,68 +1,257 @@
 use std::{
-    fs::{File},
-    io::{Read},
-    path::PathBuf
+    fs::{self, File},
+    io::{Read, Write},
+    path::{Path, PathBuf}
 };
-use std::collections::HashMap;
 use std::hash::Hash;
 use std::ops::{Deref, DerefMut};
-use egui::{Ui};
-use rand::Rng;
-use ron::{
-    de::from_reader,
-    ser::{PrettyConfig, to_writer_pretty}
-};
 use serde::{
     Deserialize,
     Serialize
 };
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+// Lets the code generated by `rl23-map-format-derive` name this crate from inside of it.
+extern crate self as rl23_map_format;
+
+mod autotile;
+mod binary;
+mod blend;
+mod canonical;
+#[cfg(feature = "editor-ui")]
+mod editor_ui;
+mod entity;
+mod error;
+mod footprint;
+mod grid;
+mod light;
+mod loot;
+mod map_file;
+mod metadata;
+mod migration;
+mod passability;
+mod region;
+mod pathfinding;
+mod random;
+mod resize;
+mod spawn;
+mod tileset;
+mod units;
+mod trigger;
+mod validation;
+mod visibility;
+
+pub use autotile::{Autotile, BlobMask, BLOB_ATLAS_COLUMNS, BLOB_TILE_COUNT, TILE_SIZE};
+pub use binary::MAX_BINARY_MAP_TILES;
+pub use blend::TerrainBlend;
+#[cfg(feature = "editor-ui")]
+pub use editor_ui::{
+    complete_entity_pick,
+    entity_pick_pending,
+    pick_entity_field,
+    EntityComponentDataImpl,
+    FieldHints,
+    InspectorField
+};
+#[cfg(feature = "editor-ui")]
+pub use egui;
+pub use rl23_map_format_derive::{ComponentRegistry, EntityComponent};
+pub use entity::{EntityId, EntityTable, PlacedEntity};
+pub use error::MapFormatError;
+pub use footprint::{FootprintCell, PlacementConflict, MAX_FOOTPRINT_REACH};
+pub use grid::{Grid, GridBounds, SparseGrid, TilePos};
+pub use light::{
+    LightLevel,
+    LightMap,
+    Light,
+    ShadeRamps,
+    FIRE_LIGHT_COLOR,
+    LAVA_LIGHT_COLOR,
+    LIGHT_LEVELS,
+    MAX_LIGHT_LEVEL
+};
+pub use loot::{
+    DropFrequency,
+    LootDrop,
+    LootEntry,
+    LootSource,
+    LootTable,
+    LootTables,
+    MAX_LOOT_DEPTH,
+    MAX_NESTED_LOOT_QUANTITY
+};
+pub use metadata::{Biome, MapMetadata};
+pub use passability::{MoveCost, MovementProfile, BASE_MOVE_COST};
+pub use region::{Region, RegionId, RegionShape};
+pub use pathfinding::{find_path, CostGrid, DistanceMap, PathOptions, TilePath};
+pub use random::{map_rng, MapRng};
+pub use resize::{ResizeAnchor, ResizeReport};
+pub use spawn::{
+    GroupMember,
+    SpawnCandidate,
+    SpawnChoice,
+    SpawnPool,
+    SpawnPoolEntry,
+    SpawnResolver,
+    Spawner,
+    DEFAULT_SPAWN_POOL,
+    NO_SPAWN_TAG
+};
+pub use tileset::{Tileset, DEFAULT_TILESET};
+pub use trigger::{
+    Activate,
+    OnEnterTrigger,
+    OnInteract,
+    RequireFlag,
+    SetFlag,
+    StartDialogue,
+    Teleport,
+    TriggerArea
+};
+use map_file::MapFile;
+pub use units::{
+    AiArchetype,
+    Faction,
+    LocalizedText,
+    UnitDefinition,
+    UnitDefinitions,
+    UnitTag,
+    DEFAULT_LANGUAGE,
+    DEFAULT_UNIT_DEFINITIONS
+};
+pub use validation::{Severity, ValidationIssue, ValidationIssueKind};
+pub use visibility::{bresenham_line, field_of_view, line_of_sight};
+
+/// Version written by `MapInfo::save_to_path`. Older maps are migrated on load.
+pub const CURRENT_FORMAT_VERSION: u32 = 8;
+
+/// Storage format of a map file, chosen by its extension.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MapFormat {
+    Ron,
+    Binary
+}
+
+impl MapFormat {
+    pub const BINARY_EXTENSION: &'static str = "rlmap";
+
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext == Self::BINARY_EXTENSION => MapFormat::Binary,
+            _ => MapFormat::Ron
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct MapInfo {
-    #[serde(default)]
     pub id_generator: IdGenerator,
-    pub width: usize,
-    pub height: usize,
-    pub terrain_layer: Vec<TerrainKind>,
-    #[serde(default)]
-    pub gatherable_layer: HashMap<usize, GatherableItem>,
-    #[serde(default)]
-    pub entity_layer: HashMap<usize, MapEntity>,
-    #[serde(default)]
-    pub entity_data_layer: HashMap<usize, EntityComponentDataList>,
-    pub wall_layer: Vec<Option<WallKind>>,
+    pub terrain_layer: Grid<TerrainKind>,
+    pub gatherable_layer: SparseGrid<GatherableItem>,
+    pub entities: EntityTable,
+    pub wall_layer: Grid<Option<WallKind>>,
+    /// Overrides the blend order of the tileset for this map.
+    pub terrain_blend: Option<TerrainBlend>,
+    /// Light level of tiles no light source reaches.
+    pub ambient_light: LightLevel,
+    /// Named areas in id order. Regions may overlap each other.
+    pub regions: Vec<Region>,
+    pub metadata: MapMetadata,
 }
 impl MapInfo {
-    pub fn create_new(width: usize, height: usize) -> Self {
-        let size = width * height;
-        let mut rng = rand::thread_rng();
-        let wall_layer = vec![None; size];
-        let mut terrain_layer = Vec::with_capacity(size);
-        for _ in 0..size {
-            terrain_layer.push(TerrainKind::Mud { offset: rng.gen_range(0..12) })
-        }
+    /// Creates a map covered in random mud. The same `seed` always gives the same map,
+    /// it is kept in the metadata so the map can be regenerated.
+    pub fn create_new(width: usize, height: usize, seed: u64) -> Self {
+        let mut rng = map_rng(seed);
         Self {
             id_generator: Default::default(),
-            width,
-            height,
-            terrain_layer,
-            gatherable_layer: Default::default(),
-            entity_layer: Default::default(),
-            entity_data_layer: Default::default(),
-            wall_layer
+            terrain_layer: Grid::from_fn(width, height, |_| TerrainKind::random_mud(&mut rng)),
+            gatherable_layer: SparseGrid::new(width, height),
+            entities: EntityTable::new(width, height),
+            wall_layer: Grid::new(width, height, None),
+            terrain_blend: None,
+            ambient_light: MAX_LIGHT_LEVEL,
+            regions: Vec::new(),
+            metadata: MapMetadata {
+                seed,
+                ..Default::default()
+            }
         }
     }
 
-    pub fn read_from_path(path: &PathBuf) -> Self {
+    pub fn width(&self) -> usize {
+        self.terrain_layer.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.terrain_layer.height()
+    }
+
+    pub fn bounds(&self) -> GridBounds {
+        self.terrain_layer.bounds()
+    }
+
+    pub fn read_from_path(path: &PathBuf) -> Result<Self, MapFormatError> {
+        let (map_info, _) = Self::read_and_migrate(path)?;
+        Ok(map_info)
+    }
+
+    /// Rewrites the map at `path` in the current format version if it is stored in an older one.
+    /// Returns the version the file had before.
+    pub fn migrate_file(path: &PathBuf) -> Result<u32, MapFormatError> {
+        let (map_info, original_version) = Self::read_and_migrate(path)?;
+        if original_version != CURRENT_FORMAT_VERSION {
+            map_info.save_to_path(path)?;
+        }
+        Ok(original_version)
+    }
+
+    fn read_and_migrate(path: &PathBuf) -> Result<(Self, u32), MapFormatError> {
         let mut bytes = Vec::new();
-        let mut file = File::open(path).unwrap();
-        file.read_to_end(&mut bytes).unwrap();
-        from_reader(&bytes[..]).unwrap()
+        let mut file = File::open(path)?;
+        file.read_to_end(&mut bytes)?;
+        let (map_file, original_version) = match MapFormat::from_path(path) {
+            MapFormat::Ron => migration::load_and_migrate(&bytes)?,
+            MapFormat::Binary => (binary::from_bytes(&bytes)?, CURRENT_FORMAT_VERSION)
+        };
+        Ok((map_file.try_into()?, original_version))
+    }
+
+    /// Writes the map next to `path` first and renames it over the original afterwards,
+    /// so a failed write never leaves a truncated map behind.
+    pub fn save_to_path(&self, path: &PathBuf) -> Result<(), MapFormatError> {
+        let mut tmp_file_name = path.file_name().unwrap_or_default().to_os_string();
+        tmp_file_name.push(".tmp");
+        let tmp_path = path.with_file_name(tmp_file_name);
+
+        let result = self.write_to_path(&tmp_path, MapFormat::from_path(path)).and_then(|_| {
+            fs::rename(&tmp_path, path)?;
+            Ok(())
+        });
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+        result
     }
 
-    pub fn save_to_path(&self, path: &PathBuf) {
-        let mut file = File::create(path).unwrap();
-        to_writer_pretty(&mut file, self, PrettyConfig::new()).unwrap();
+    /// Canonical RON text of the map, as written by `save_to_path`.
+    pub fn to_canonical_string(&self) -> Result<String, MapFormatError> {
+        canonical::to_canonical_string(&MapFile::from(self))
+    }
+
+    /// Binary encoding of the map, as written by `save_to_path` for `.rlmap` files.
+    pub fn to_binary(&self) -> Result<Vec<u8>, MapFormatError> {
+        binary::to_bytes(&MapFile::from(self))
+    }
+
+    fn write_to_path(&self, path: &PathBuf, format: MapFormat) -> Result<(), MapFormatError> {
+        let bytes = match format {
+            MapFormat::Ron => self.to_canonical_string()?.into_bytes(),
+            MapFormat::Binary => self.to_binary()?
+        };
+        let mut file = File::create(path)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        Ok(())
     }
 }
 
@@ -78,13 +267,19 @@ impl IdGenerator {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum WallKind {
     Dirt,
     Bricks,
     Wood
 }
 impl WallKind {
+    pub const ALL: [WallKind; 3] = [
+        WallKind::Dirt,
+        WallKind::Bricks,
+        WallKind::Wood
+    ];
+
     pub fn get_tiling_info(self) -> TilingInfo {
         match self {
             WallKind::Dirt => TilingInfo::Wang(WangTerrain{
@@ -103,7 +298,7 @@ impl WallKind {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum TerrainKind {
     Mud { offset: usize },
     Sand,
@@ -119,6 +314,20 @@ pub enum TerrainKind {
 }
 
 impl TerrainKind {
+    /// Every kind except `Mud`, which has a variant per tile instead of a single kind.
+    pub const WANG_KINDS: [TerrainKind; 10] = [
+        TerrainKind::Sand,
+        TerrainKind::Dirt,
+        TerrainKind::Grass,
+        TerrainKind::Water,
+        TerrainKind::CaveWater,
+        TerrainKind::Lava,
+        TerrainKind::Tile,
+        TerrainKind::BrightTile,
+        TerrainKind::MossTile,
+        TerrainKind::VibrantTile
+    ];
+
     pub fn get_tiling_info(self) -> TilingInfo {
         match self {
             TerrainKind::Mud { offset } => TilingInfo::Mud(MudTerrain { offset }),
@@ -166,13 +375,13 @@ impl TerrainKind {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum TilingInfo {
     Wang(WangTerrain),
     Mud(MudTerrain)
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct WangTerrain{ pub x_offset: usize, pub y_offset: usize }
 impl WangTerrain{
     pub fn get_final_coords(self, encoding: WangEncoding) -> Option<[usize; 2]> {
@@ -273,7 +482,7 @@ impl WangTerrain{
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct MudTerrain { offset: usize }
 impl MudTerrain {
     pub fn get_variant_coords_by_offset(self) -> [usize; 2] {
@@ -307,7 +516,7 @@ pub struct WangEncoding {
     pub south_west: bool
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum MapEntity {
     Door,
     ClosedDoor(ClosedDoor),
@@ -318,7 +527,7 @@ pub enum MapEntity {
     Logic
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct EntityDrawCommand {
     pub coords: [u16; 2],
     pub size: [u16; 2],
@@ -361,7 +570,7 @@ impl MapEntity {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
 pub enum Unit {
     Leshy,
     MushroomMan,
@@ -409,6 +618,52 @@ pub enum Unit {
 }
 
 impl Unit {
+    pub const ALL: [Unit; 43] = [
+        Unit::Leshy,
+        Unit::MushroomMan,
+        Unit::DarkWolf,
+        Unit::Wolf,
+        Unit::RogueKnife,
+        Unit::RogueAxe,
+        Unit::SnakeHuge,
+        Unit::Snake,
+        Unit::Squirrel,
+        Unit::Stump,
+        Unit::Czort,
+        Unit::Imp,
+        Unit::Spider,
+        Unit::Bat,
+        Unit::Ghost,
+        Unit::Skeleton1,
+        Unit::Skeleton2,
+        Unit::Necromancer,
+        Unit::DarkVigilante,
+        Unit::DarkWarlord,
+        Unit::Volkolak,
+        Unit::Gorynich,
+        Unit::Rusalka,
+        Unit::Vodyanoy,
+        Unit::Liho,
+        Unit::Polevik,
+        Unit::Poludenniza,
+        Unit::PeasantMale1,
+        Unit::PeasantMale2,
+        Unit::PeasantMale3,
+        Unit::PeasantMale4,
+        Unit::PeasantFemale1,
+        Unit::PeasantFemale2,
+        Unit::PeasantFemale3,
+        Unit::PeasantFemale4,
+        Unit::PeasantFemale5,
+        Unit::PeasantFemale6,
+        Unit::PeasantFemale7,
+        Unit::PeasantFemale8,
+        Unit::PeasantFighter,
+        Unit::PeasantArcher,
+        Unit::SorcererWhite,
+        Unit::SorcererRed
+    ];
+
     pub fn get_draw_command(self) -> EntityDrawCommand {
         match self {
             Unit::Leshy => EntityDrawCommand{
@@ -478,7 +733,7 @@ impl Unit {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum Decor {
     Bed1GreenLeft,
     Bed1GreenRight,
@@ -508,6 +763,29 @@ pub enum Decor {
 }
 
 impl Decor {
+    pub const ALL: [Decor; 20] = [
+        Decor::Bed1GreenLeft,
+        Decor::Bed1GreenRight,
+        Decor::Bed2GreenLeft,
+        Decor::Bed2GreenRight,
+        Decor::Bed1BlueLeft,
+        Decor::Bed1BlueRight,
+        Decor::Bed2BlueLeft,
+        Decor::Bed2BlueRight,
+        Decor::Bed1WhiteLeft,
+        Decor::Bed1WhiteRight,
+        Decor::Bed2WhiteLeft,
+        Decor::Bed2WhiteRight,
+        Decor::TableGreen,
+        Decor::TableBlue,
+        Decor::TableBlack,
+        Decor::OvenLeft,
+        Decor::OvenRight,
+        Decor::Closet,
+        Decor::Dresser1,
+        Decor::Dresser2
+    ];
+
     pub fn get_draw_command(self) -> EntityDrawCommand {
         match self {
             Decor::Bed1GreenLeft => EntityDrawCommand {
@@ -654,7 +932,7 @@ impl Decor {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum Tree {
     Pine1,
     Pine2,
@@ -667,6 +945,17 @@ pub enum Tree {
 }
 
 impl Tree {
+    pub const ALL: [Tree; 8] = [
+        Tree::Pine1,
+        Tree::Pine2,
+        Tree::Oak,
+        Tree::Birch,
+        Tree::Pine1Cursed,
+        Tree::Pine2Cursed,
+        Tree::OakCursed,
+        Tree::BirchCursed
+    ];
+
     pub fn get_draw_command(self) -> EntityDrawCommand {
         match self {
             Tree::Pine1 => EntityDrawCommand {
@@ -729,7 +1018,7 @@ impl Tree {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum ClosedDoor {
     Gray,
     Green,
@@ -738,6 +1027,13 @@ pub enum ClosedDoor {
 }
 
 impl ClosedDoor {
+    pub const ALL: [ClosedDoor; 4] = [
+        ClosedDoor::Gray,
+        ClosedDoor::Green,
+        ClosedDoor::Brown,
+        ClosedDoor::Blue
+    ];
+
     pub fn get_draw_command(self) -> EntityDrawCommand {
         match self {
             ClosedDoor::Gray => EntityDrawCommand {
@@ -764,7 +1060,7 @@ impl ClosedDoor {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum GatherableItem {
     Mushroom(usize),
     Wheat
@@ -788,6 +1084,7 @@ impl GatherableItem {
     }
 }
 
+/// Component list of an entity as stored by format versions before 3.
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct EntityComponentDataList {
     id: u64,
@@ -825,74 +1122,24 @@ impl DerefMut for EntityComponentDataList {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+/// Components of entities and regions. Every variant shows up in the editor's "+" menu.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, ComponentRegistry)]
 pub enum EntityComponentData {
-    SpawnRandomUnit(SpawnRandomUnit)
-}
-
-impl EntityComponentData {
-    pub fn draw_context_menu(map_entity: MapEntity, id_generator: &mut IdGenerator, ui: &mut egui::Ui) -> Option<Self> {
-        let mut result = None;
-        ui.menu_button("+", |ui: &mut egui::Ui| {
-            macro_rules! menu_entry(
-                ($type_name:ident as $name: literal) => {
-                    if $type_name::is_applicable_for_enitity_type(map_entity) {
-                        if ui.button($name).clicked() {
-                            result = Some(Self::$type_name($type_name::make_default(id_generator)));
-                        }
-                    }
-                }
-            );
-            menu_entry!(SpawnRandomUnit as "Spawn Random Unit");
-        });
-        result
-    }
-
-    pub fn draw_egui(&mut self, id_generator: &mut IdGenerator, ui: &mut egui::Ui) -> bool {
-        let mut delete = false;
-        match self {
-            EntityComponentData::SpawnRandomUnit(spawn_random_unit) =>
-                spawn_random_unit.draw_egui(id_generator, ui)
-        }
-        if ui.button("DELETE").clicked() {
-            delete = true;
-        }
-        ui.separator();
-        !delete
-    }
-}
-
-pub trait EntityComponentDataImpl: Clone + Default {
-    fn is_applicable_for_enitity_type(map_entity: MapEntity) -> bool;
-    fn draw_egui(&mut self, id_generator: &mut IdGenerator, ui: &mut egui::Ui);
-    fn make_default(id_generator: &mut IdGenerator) -> Self;
+    SpawnRandomUnit(SpawnRandomUnit),
+    Light(Light),
+    OnEnterTrigger(OnEnterTrigger),
+    OnInteract(OnInteract),
+    StartDialogue(StartDialogue),
+    SetFlag(SetFlag),
+    RequireFlag(RequireFlag),
+    Teleport(Teleport),
+    Activate(Activate),
+    LootTable(LootTable)
 }
 
-#[derive(Default, Copy, Clone, PartialEq, Debug, Deserialize, Serialize)]
+#[derive(Default, Copy, Clone, PartialEq, Debug, Deserialize, Serialize, EntityComponent)]
+#[component(applies_to(Logic))]
 pub struct SpawnRandomUnit {
     pub min_level: i32,
     pub max_level: i32
 }
-
-impl EntityComponentDataImpl for SpawnRandomUnit {
-    fn draw_egui(&mut self, _id_generator: &mut IdGenerator, ui: &mut Ui) {
-            ui.label(padded_str("Spawn Random Unit"));
-            ui.add(egui::DragValue::new(&mut self.min_level).prefix("min_level: ").speed(1.0));
-            ui.add(egui::DragValue::new(&mut self.max_level).prefix("max_level: ").speed(1.0));
-    }
-
-    fn is_applicable_for_enitity_type(map_entity: MapEntity) -> bool {
-        match map_entity {
-            MapEntity::Logic => true,
-            _ => false
-        }
-    }
-
-    fn make_default(_id_generator: &mut IdGenerator) -> Self {
-        Default::default()
-    }
-}
-
-fn padded_str(s: &str) -> String {
-    format!("{:<35}", s)
-}
\ No newline at end of file
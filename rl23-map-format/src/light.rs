@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+use crate::{
+    field_of_view,
+    EntityComponent,
+    EntityComponentData,
+    Grid,
+    MapInfo,
+    TerrainKind,
+    TilePos
+};
+
+/// Brightness of a tile, from pitch dark at 0 to fully lit at `MAX_LIGHT_LEVEL`.
+pub type LightLevel = u8;
+
+pub const MAX_LIGHT_LEVEL: LightLevel = 15;
+
+/// Number of steps of every shade ramp.
+pub const LIGHT_LEVELS: usize = MAX_LIGHT_LEVEL as usize + 1;
+
+/// Warm yellow of `tiles.im256`, the colour of fire and candles.
+pub const FIRE_LIGHT_COLOR: u8 = 8;
+
+/// Orange of `tiles.im256`, the glow of lava.
+pub const LAVA_LIGHT_COLOR: u8 = 5;
+
+/// Light component of an entity, also emitted by glowing terrain. Its brightness fades linearly to nothing past `radius`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize, EntityComponent)]
+#[component(except(Loot))]
+pub struct Light {
+    #[component(range = 0..=16)]
+    pub radius: u8,
+    /// Palette index of the light colour.
+    #[component(range = 0..=255)]
+    pub color_index: u8,
+    #[component(range = 0..=MAX_LIGHT_LEVEL)]
+    pub intensity: LightLevel
+}
+
+impl Default for Light {
+    fn default() -> Self {
+        Self {
+            radius: 4,
+            color_index: FIRE_LIGHT_COLOR,
+            intensity: MAX_LIGHT_LEVEL
+        }
+    }
+}
+
+impl Light {
+    /// Light reaching a tile `dx` and `dy` tiles away, ignoring obstacles.
+    pub fn level_at(self, dx: usize, dy: usize) -> LightLevel {
+        let radius = self.radius as usize;
+        let distance_squared = dx * dx + dy * dy;
+        if distance_squared > radius * (radius + 1) {
+            return 0;
+        }
+        let distance = (distance_squared as f64).sqrt() as usize;
+        let intensity = self.intensity.min(MAX_LIGHT_LEVEL) as usize;
+        (intensity * (radius + 1 - distance.min(radius + 1)) / (radius + 1)) as LightLevel
+    }
+}
+
+impl TerrainKind {
+    /// Light glowing from the terrain itself.
+    pub fn light(self) -> Option<Light> {
+        match self {
+            TerrainKind::Lava => Some(Light {
+                radius: 3,
+                color_index: LAVA_LIGHT_COLOR,
+                intensity: 10
+            }),
+            _ => None
+        }
+    }
+}
+
+/// Light level of every tile, together with the colour of the light shining on it brightest.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LightMap {
+    levels: Grid<LightLevel>,
+    colors: Grid<Option<u8>>
+}
+
+impl LightMap {
+    pub fn level(&self, pos: TilePos) -> Option<LightLevel> {
+        self.levels.get(pos).copied()
+    }
+
+    /// `None` for tiles lit by the ambient light only.
+    pub fn color(&self, pos: TilePos) -> Option<u8> {
+        self.colors.get(pos).copied().flatten()
+    }
+
+    pub fn levels(&self) -> &Grid<LightLevel> {
+        &self.levels
+    }
+}
+
+/// Palette indices of every palette colour darkened to each light level.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShadeRamps {
+    ramps: Vec<[u8; LIGHT_LEVELS]>
+}
+
+impl ShadeRamps {
+    pub fn new(palette: &[[u8; 3]]) -> Self {
+        let closest = |color: [u32; 3]| -> u8 {
+            let distance = |entry: &[u8; 3]| -> u32 {
+                (0..3).map(|i| (entry[i] as i32 - color[i] as i32).pow(2) as u32).sum()
+            };
+            (0..palette.len()).min_by_key(|&i| distance(&palette[i])).unwrap_or(0) as u8
+        };
+        let ramps = palette
+            .iter()
+            .map(|&[r, g, b]| {
+                let mut ramp = [0; LIGHT_LEVELS];
+                for (level, shade) in ramp.iter_mut().enumerate() {
+                    let darken = |channel: u8| channel as u32 * level as u32 / MAX_LIGHT_LEVEL as u32;
+                    *shade = closest([darken(r), darken(g), darken(b)]);
+                }
+                ramp
+            })
+            .collect();
+        Self { ramps }
+    }
+
+    /// The colour standing for `color_index` on a tile lit at `level`.
+    pub fn shade(&self, color_index: u8, level: LightLevel) -> u8 {
+        match self.ramps.get(color_index as usize) {
+            Some(ramp) => ramp[level.min(MAX_LIGHT_LEVEL) as usize],
+            None => color_index
+        }
+    }
+}
+
+impl MapInfo {
+    /// Every light of the map: glowing terrain in row-major order, then lights of entities in id order.
+    pub fn light_sources(&self) -> Vec<(TilePos, Light)> {
+        let terrain_lights = self.terrain_layer
+            .iter()
+            .filter_map(|(pos, terrain)| terrain.light().map(|light| (pos, light)));
+        let entity_lights = self.entities.iter().flat_map(|placed| {
+            placed.components.iter().filter_map(move |component| match component {
+                EntityComponentData::Light(light) => Some((placed.position(), *light)),
+                _ => None
+            })
+        });
+        terrain_lights.chain(entity_lights).collect()
+    }
+
+    /// Light of every tile: the ambient level plus every source shining on the tile.
+    /// Walls stop light, but are lit themselves.
+    pub fn light_map(&self) -> LightMap {
+        let mut levels = Grid::new(self.width(), self.height(), self.ambient_light.min(MAX_LIGHT_LEVEL));
+        let mut colors: Grid<Option<(LightLevel, u8)>> = Grid::new(self.width(), self.height(), None);
+        let walls = Grid::from_fn(self.width(), self.height(), |pos| {
+            self.wall_layer.get(pos).unwrap().is_some()
+        });
+
+        for (origin, light) in self.light_sources() {
+            for pos in field_of_view(&walls, origin, light.radius as usize) {
+                let level = light.level_at(pos.x.abs_diff(origin.x), pos.y.abs_diff(origin.y));
+                if level == 0 {
+                    continue;
+                }
+                let total = levels.get_mut(pos).unwrap();
+                *total = total.saturating_add(level).min(MAX_LIGHT_LEVEL);
+                let color = colors.get_mut(pos).unwrap();
+                if color.is_none_or(|(brightest, _)| level > brightest) {
+                    *color = Some((level, light.color_index));
+                }
+            }
+        }
+
+        LightMap {
+            levels,
+            colors: Grid::from_fn(self.width(), self.height(), |pos| {
+                colors.get(pos).unwrap().map(|(_, color)| color)
+            })
+        }
+    }
+}
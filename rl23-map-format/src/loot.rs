@@ -0,0 +1,266 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::Read,
+    path::PathBuf
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::{EntityComponent, MapFormatError};
+
+/// Nesting depth past which a table reference is taken for a reference cycle.
+pub const MAX_LOOT_DEPTH: usize = 16;
+/// Most times a single entry may roll a nested table.
+pub const MAX_NESTED_LOOT_QUANTITY: u32 = 100;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum LootSource {
+    /// Makes the other entries of a table less likely to drop.
+    #[default]
+    Nothing,
+    Item(String),
+    /// Rolls a table of `LootTables`, once per unit of quantity.
+    Table(String)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LootEntry {
+    pub source: LootSource,
+    /// Chance of the entry relative to the other entries, unused for guaranteed drops.
+    pub weight: u32,
+    pub min_quantity: u32,
+    pub max_quantity: u32
+}
+
+impl Default for LootEntry {
+    fn default() -> Self {
+        Self {
+            source: LootSource::Nothing,
+            weight: 1,
+            min_quantity: 1,
+            max_quantity: 1
+        }
+    }
+}
+
+/// Contents of a loot container or of what a unit drops. Every guaranteed entry drops,
+/// then `rolls` entries are picked by weight. A component naming a shared table
+/// holds just a guaranteed `LootSource::Table` entry.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, EntityComponent)]
+#[component(label = "Loot Table", applies_to(Loot, Unit))]
+pub struct LootTable {
+    #[component(range = 0..=16)]
+    pub rolls: u32,
+    #[serde(default)]
+    pub guaranteed: Vec<LootEntry>,
+    #[serde(default)]
+    pub entries: Vec<LootEntry>
+}
+
+impl Default for LootTable {
+    fn default() -> Self {
+        Self {
+            rolls: 1,
+            guaranteed: Vec::new(),
+            entries: Vec::new()
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LootDrop {
+    pub item: String,
+    pub quantity: u32
+}
+
+/// How often an item dropped over many rolls of a table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DropFrequency {
+    pub item: String,
+    /// Share of the rolls the item dropped in.
+    pub chance: f64,
+    /// Quantity of the item per roll it dropped in.
+    pub average_quantity: f64
+}
+
+/// Named tables shared by every map, read from a RON map of names to tables.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct LootTables {
+    pub tables: BTreeMap<String, LootTable>
+}
+
+impl LootTables {
+    pub fn read_from_path(path: &PathBuf) -> Result<Self, MapFormatError> {
+        let mut bytes = Vec::new();
+        let mut file = File::open(path)?;
+        file.read_to_end(&mut bytes)?;
+        let tables: Self = ron::de::from_bytes(&bytes)?;
+        tables.validate()?;
+        Ok(tables)
+    }
+
+    /// Checks that every `LootSource::Table` names a table of `self`, is rolled at most
+    /// `MAX_NESTED_LOOT_QUANTITY` times and that no table refers back to itself,
+    /// however unlikely the entries leading there are to be rolled.
+    pub fn validate(&self) -> Result<(), MapFormatError> {
+        let mut checked = BTreeSet::new();
+        for name in self.tables.keys() {
+            self.validate_table(name, &mut Vec::new(), &mut checked)?;
+        }
+        Ok(())
+    }
+
+    fn validate_table<'a>(
+        &'a self,
+        name: &'a str,
+        stack: &mut Vec<&'a str>,
+        checked: &mut BTreeSet<&'a str>
+    ) -> Result<(), MapFormatError> {
+        if checked.contains(name) {
+            return Ok(());
+        }
+        if let Some(start) = stack.iter().position(|&visited| visited == name) {
+            let mut names: Vec<String> = stack[start..].iter().map(|visited| visited.to_string()).collect();
+            names.push(name.to_string());
+            return Err(MapFormatError::LootTableCycle { names });
+        }
+        let table = self.get(name).ok_or_else(|| MapFormatError::UnknownLootTable {
+            name: name.to_string()
+        })?;
+        stack.push(name);
+        self.validate_references(table, stack, checked)?;
+        stack.pop();
+        checked.insert(name);
+        Ok(())
+    }
+
+    /// Checks the tables `table` refers to, for a table that is not one of `self`,
+    /// like the one of an entity component.
+    pub fn validate_table_references(&self, table: &LootTable) -> Result<(), MapFormatError> {
+        self.validate_references(table, &mut Vec::new(), &mut BTreeSet::new())
+    }
+
+    fn validate_references<'a>(
+        &'a self,
+        table: &'a LootTable,
+        stack: &mut Vec<&'a str>,
+        checked: &mut BTreeSet<&'a str>
+    ) -> Result<(), MapFormatError> {
+        for entry in table.guaranteed.iter().chain(table.entries.iter()) {
+            if let LootSource::Table(nested) = &entry.source {
+                entry.check_nested_quantity(nested)?;
+                self.validate_table(nested, stack, checked)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LootTable> {
+        self.tables.get(name)
+    }
+}
+
+impl LootEntry {
+    fn check_nested_quantity(&self, name: &str) -> Result<(), MapFormatError> {
+        let quantity = self.max_quantity.max(self.min_quantity);
+        if quantity > MAX_NESTED_LOOT_QUANTITY {
+            return Err(MapFormatError::LootQuantityTooLarge { name: name.to_string(), quantity });
+        }
+        Ok(())
+    }
+
+    fn drop_into(
+        &self,
+        tables: &LootTables,
+        rng: &mut impl Rng,
+        depth: usize,
+        drops: &mut Vec<LootDrop>
+    ) -> Result<(), MapFormatError> {
+        let quantity = rng.gen_range(self.min_quantity..=self.max_quantity.max(self.min_quantity));
+        match &self.source {
+            LootSource::Nothing => {}
+            LootSource::Item(_) if quantity == 0 => {}
+            LootSource::Item(item) => match drops.iter_mut().find(|drop| &drop.item == item) {
+                Some(drop) => drop.quantity = drop.quantity.saturating_add(quantity),
+                None => drops.push(LootDrop { item: item.clone(), quantity })
+            },
+            LootSource::Table(name) => {
+                let table = tables.get(name).ok_or_else(|| MapFormatError::UnknownLootTable {
+                    name: name.clone()
+                })?;
+                if depth >= MAX_LOOT_DEPTH {
+                    return Err(MapFormatError::LootTableTooDeep { name: name.clone() });
+                }
+                self.check_nested_quantity(name)?;
+                for _ in 0..quantity {
+                    table.roll_into(tables, rng, depth + 1, drops)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl LootTable {
+    /// Rolls the table. The same `rng` state always gives the same drops,
+    /// listed in the order they first dropped in.
+    pub fn roll(&self, tables: &LootTables, rng: &mut impl Rng) -> Result<Vec<LootDrop>, MapFormatError> {
+        let mut drops = Vec::new();
+        self.roll_into(tables, rng, 0, &mut drops)?;
+        Ok(drops)
+    }
+
+    fn roll_into(
+        &self,
+        tables: &LootTables,
+        rng: &mut impl Rng,
+        depth: usize,
+        drops: &mut Vec<LootDrop>
+    ) -> Result<(), MapFormatError> {
+        for entry in self.guaranteed.iter() {
+            entry.drop_into(tables, rng, depth, drops)?;
+        }
+        let total_weight: u64 = self.entries.iter().map(|entry| entry.weight as u64).sum();
+        if total_weight == 0 {
+            return Ok(());
+        }
+        for _ in 0..self.rolls {
+            let mut pick = rng.gen_range(0..total_weight);
+            for entry in self.entries.iter() {
+                if pick < entry.weight as u64 {
+                    entry.drop_into(tables, rng, depth, drops)?;
+                    break;
+                }
+                pick -= entry.weight as u64;
+            }
+        }
+        Ok(())
+    }
+
+    /// Rolls the table `rolls` times, for previews and balancing.
+    /// Items are ordered by name.
+    pub fn drop_frequencies(
+        &self,
+        tables: &LootTables,
+        rng: &mut impl Rng,
+        rolls: usize
+    ) -> Result<Vec<DropFrequency>, MapFormatError> {
+        let mut totals: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+        for _ in 0..rolls {
+            for drop in self.roll(tables, rng)? {
+                let (times, quantity) = totals.entry(drop.item).or_default();
+                *times += 1;
+                *quantity += drop.quantity as u64;
+            }
+        }
+        Ok(totals
+            .into_iter()
+            .map(|(item, (times, quantity))| DropFrequency {
+                item,
+                chance: times as f64 / rolls as f64,
+                average_quantity: quantity as f64 / times as f64
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::{
+    EntityTable,
+    GatherableItem,
+    Grid,
+    GridBounds,
+    IdGenerator,
+    LightLevel,
+    MapFormatError,
+    MapInfo,
+    MapMetadata,
+    PlacedEntity,
+    Region,
+    SparseGrid,
+    TerrainBlend,
+    TerrainKind,
+    WallKind,
+    CURRENT_FORMAT_VERSION,
+    MAX_LIGHT_LEVEL
+};
+
+/// On-disk layout of the current format version.
+/// Layers are stored flat and keyed by `y * width + x`, entities are stored in id order.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename = "MapInfo")]
+pub(crate) struct MapFile {
+    #[serde(default)]
+    pub format_version: u32,
+    #[serde(default)]
+    pub id_generator: IdGenerator,
+    pub width: usize,
+    pub height: usize,
+    pub terrain_layer: Vec<TerrainKind>,
+    #[serde(default)]
+    pub gatherable_layer: HashMap<usize, GatherableItem>,
+    #[serde(default)]
+    pub entities: Vec<PlacedEntity>,
+    pub wall_layer: Vec<Option<WallKind>>,
+    #[serde(default)]
+    pub terrain_blend: Option<TerrainBlend>,
+    #[serde(default = "fully_lit")]
+    pub ambient_light: LightLevel,
+    #[serde(default)]
+    pub regions: Vec<Region>,
+    #[serde(default)]
+    pub metadata: MapMetadata,
+}
+
+/// Maps made before lighting existed show everything at full brightness.
+pub(crate) fn fully_lit() -> LightLevel {
+    MAX_LIGHT_LEVEL
+}
+
+fn dense_layer<T>(
+    layer: &'static str,
+    bounds: GridBounds,
+    cells: Vec<T>
+) -> Result<Grid<T>, MapFormatError> {
+    Grid::from_vec(bounds.width, bounds.height, cells).map_err(|cells| {
+        MapFormatError::LayerSizeMismatch {
+            layer,
+            expected: bounds.len(),
+            actual: cells.len()
+        }
+    })
+}
+
+pub(crate) fn sparse_layer<T>(
+    layer: &'static str,
+    bounds: GridBounds,
+    cells: HashMap<usize, T>
+) -> Result<SparseGrid<T>, MapFormatError> {
+    let mut grid = SparseGrid::new(bounds.width, bounds.height);
+    for (idx, value) in cells {
+        let pos = bounds.pos_of(idx).ok_or(MapFormatError::KeyOutOfBounds { layer, idx })?;
+        grid.insert(pos, value);
+    }
+    Ok(grid)
+}
+
+fn flat_layer<T: Clone>(grid: &SparseGrid<T>) -> HashMap<usize, T> {
+    let bounds = grid.bounds();
+    grid.iter()
+        .map(|(pos, value)| (bounds.index_of(pos).unwrap(), value.clone()))
+        .collect()
+}
+
+fn entity_table(bounds: GridBounds, entities: Vec<PlacedEntity>) -> Result<EntityTable, MapFormatError> {
+    let mut table = EntityTable::new(bounds.width, bounds.height);
+    for entity in entities {
+        let (id, position) = (entity.id(), entity.position());
+        if table.contains(id) {
+            return Err(MapFormatError::DuplicateEntityId { id });
+        }
+        if !table.insert(entity) {
+            return Err(MapFormatError::EntityOutOfBounds { id, position });
+        }
+    }
+    Ok(table)
+}
+
+fn sorted_regions(mut regions: Vec<Region>) -> Result<Vec<Region>, MapFormatError> {
+    regions.sort_by_key(|region| region.id);
+    if let Some(pair) = regions.windows(2).find(|pair| pair[0].id == pair[1].id) {
+        return Err(MapFormatError::DuplicateRegionId { id: pair[0].id });
+    }
+    Ok(regions)
+}
+
+impl TryFrom<MapFile> for MapInfo {
+    type Error = MapFormatError;
+
+    fn try_from(file: MapFile) -> Result<Self, Self::Error> {
+        let bounds = GridBounds::new(file.width, file.height);
+        if bounds.checked_len().is_none() {
+            return Err(MapFormatError::MapTooLarge {
+                width: file.width,
+                height: file.height
+            });
+        }
+        Ok(MapInfo {
+            id_generator: file.id_generator,
+            terrain_layer: dense_layer("terrain_layer", bounds, file.terrain_layer)?,
+            gatherable_layer: sparse_layer("gatherable_layer", bounds, file.gatherable_layer)?,
+            entities: entity_table(bounds, file.entities)?,
+            wall_layer: dense_layer("wall_layer", bounds, file.wall_layer)?,
+            terrain_blend: file.terrain_blend,
+            ambient_light: file.ambient_light,
+            regions: sorted_regions(file.regions)?,
+            metadata: file.metadata
+        })
+    }
+}
+
+impl From<&MapInfo> for MapFile {
+    fn from(map_info: &MapInfo) -> Self {
+        MapFile {
+            format_version: CURRENT_FORMAT_VERSION,
+            id_generator: map_info.id_generator.clone(),
+            width: map_info.width(),
+            height: map_info.height(),
+            terrain_layer: map_info.terrain_layer.as_slice().to_vec(),
+            gatherable_layer: flat_layer(&map_info.gatherable_layer),
+            entities: map_info.entities.iter().cloned().collect(),
+            wall_layer: map_info.wall_layer.as_slice().to_vec(),
+            terrain_blend: map_info.terrain_blend.clone(),
+            ambient_light: map_info.ambient_light,
+            regions: map_info.regions.clone(),
+            metadata: map_info.metadata.clone()
+        }
+    }
+}
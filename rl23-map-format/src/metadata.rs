@@ -0,0 +1,47 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use crate::TilePos;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Biome {
+    #[default]
+    Forest,
+    Field,
+    Swamp,
+    Village,
+    Cave,
+    Dungeon
+}
+
+impl Biome {
+    pub const ALL: [Biome; 6] = [
+        Biome::Forest,
+        Biome::Field,
+        Biome::Swamp,
+        Biome::Village,
+        Biome::Cave,
+        Biome::Dungeon
+    ];
+}
+
+/// Everything the game needs to know about a map besides its layers.
+/// Maps written before metadata existed get the defaults.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MapMetadata {
+    /// Area name shown to the player.
+    pub name: String,
+    pub biome: Biome,
+    /// Palette the game swaps in for this map, the palette of `tiles.im256` when `None`.
+    pub ambient_palette: Option<String>,
+    pub music_cue: Option<String>,
+    pub author: String,
+    pub description: String,
+    pub recommended_level: i32,
+    /// Tile the player starts on when entering the map.
+    pub player_spawn: Option<TilePos>,
+    /// Seed the map was created with, see `map_rng`.
+    pub seed: u64,
+    /// Weights of spawn pool entries by name, replacing the weights of the pool on this map.
+    pub spawn_weights: BTreeMap<String, u32>
+}
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use ron::de::from_bytes;
+use serde::Deserialize;
+use crate::{
+    map_file::{fully_lit, MapFile},
+    EntityComponentDataList,
+    GatherableItem,
+    GridBounds,
+    IdGenerator,
+    MapEntity,
+    MapFormatError,
+    PlacedEntity,
+    TerrainBlend,
+    TerrainKind,
+    WallKind,
+    CURRENT_FORMAT_VERSION
+};
+
+#[derive(Deserialize)]
+#[serde(rename = "MapInfo")]
+struct FormatVersionProbe {
+    #[serde(default)]
+    format_version: u32
+}
+
+/// Maps written before `format_version` was introduced.
+/// Entities placed by older editors may lack an entry in `entity_data_layer`.
+#[derive(Deserialize)]
+#[serde(rename = "MapInfo")]
+struct MapInfoV0 {
+    #[serde(default)]
+    id_generator: IdGenerator,
+    width: usize,
+    height: usize,
+    terrain_layer: Vec<TerrainKind>,
+    #[serde(default)]
+    gatherable_layer: HashMap<usize, GatherableItem>,
+    #[serde(default)]
+    entity_layer: HashMap<usize, MapEntity>,
+    #[serde(default)]
+    entity_data_layer: HashMap<usize, EntityComponentDataList>,
+    wall_layer: Vec<Option<WallKind>>,
+}
+
+impl MapInfoV0 {
+    fn migrate(self) -> MapInfoV2 {
+        let Self {
+            mut id_generator,
+            width,
+            height,
+            terrain_layer,
+            gatherable_layer,
+            entity_layer,
+            mut entity_data_layer,
+            wall_layer
+        } = self;
+
+        entity_data_layer.retain(|idx, _| entity_layer.contains_key(idx));
+
+        let max_id = entity_data_layer.values().map(|data| data.id).max();
+        if let Some(max_id) = max_id {
+            id_generator.next_id = id_generator.next_id.max(max_id + 1);
+        }
+
+        let mut missing: Vec<usize> = entity_layer
+            .keys()
+            .filter(|idx| !entity_data_layer.contains_key(idx))
+            .copied()
+            .collect();
+        missing.sort_unstable();
+        for idx in missing {
+            let id = id_generator.generate();
+            entity_data_layer.insert(idx, EntityComponentDataList::create(id));
+        }
+
+        MapInfoV2 {
+            id_generator,
+            width,
+            height,
+            terrain_layer,
+            gatherable_layer,
+            entity_layer,
+            entity_data_layer,
+            wall_layer,
+            terrain_blend: None
+        }
+    }
+}
+
+/// Maps of versions 1 and 2, which keep entities and their component lists
+/// in two layers keyed by tile index. Version 1 has no `terrain_blend`.
+#[derive(Deserialize)]
+#[serde(rename = "MapInfo")]
+struct MapInfoV2 {
+    #[serde(default)]
+    id_generator: IdGenerator,
+    width: usize,
+    height: usize,
+    terrain_layer: Vec<TerrainKind>,
+    #[serde(default)]
+    gatherable_layer: HashMap<usize, GatherableItem>,
+    #[serde(default)]
+    entity_layer: HashMap<usize, MapEntity>,
+    #[serde(default)]
+    entity_data_layer: HashMap<usize, EntityComponentDataList>,
+    wall_layer: Vec<Option<WallKind>>,
+    #[serde(default)]
+    terrain_blend: Option<TerrainBlend>,
+}
+
+impl MapInfoV2 {
+    /// Moves every entity into the id-keyed entity table, taking the id from its component list.
+    /// Entities without a list, or with an id already taken, get a fresh id.
+    fn migrate(self) -> Result<MapFile, MapFormatError> {
+        let Self {
+            mut id_generator,
+            width,
+            height,
+            terrain_layer,
+            gatherable_layer,
+            entity_layer,
+            mut entity_data_layer,
+            wall_layer,
+            terrain_blend
+        } = self;
+        let bounds = GridBounds::new(width, height);
+
+        let max_id = entity_data_layer.values().map(|data| data.id).max();
+        if let Some(max_id) = max_id {
+            id_generator.next_id = id_generator.next_id.max(max_id + 1);
+        }
+
+        let mut entity_layer: Vec<(usize, MapEntity)> = entity_layer.into_iter().collect();
+        entity_layer.sort_unstable_by_key(|&(idx, _)| idx);
+
+        let mut entities: Vec<PlacedEntity> = Vec::with_capacity(entity_layer.len());
+        for (idx, entity) in entity_layer {
+            let position = bounds
+                .pos_of(idx)
+                .ok_or(MapFormatError::KeyOutOfBounds { layer: "entity_layer", idx })?;
+            let data = entity_data_layer.remove(&idx);
+            let id = match &data {
+                Some(data) if !entities.iter().any(|it| it.id() == data.id) => data.id,
+                _ => id_generator.generate()
+            };
+
+            let mut placed = PlacedEntity::new(id, position, entity);
+            if let Some(data) = data {
+                placed.components = data.components;
+            }
+            entities.push(placed);
+        }
+        entities.sort_unstable_by_key(|entity| entity.id());
+
+        Ok(MapFile {
+            format_version: 3,
+            id_generator,
+            width,
+            height,
+            terrain_layer,
+            gatherable_layer,
+            entities,
+            wall_layer,
+            terrain_blend,
+            ambient_light: fully_lit(),
+            regions: Vec::new(),
+            metadata: Default::default()
+        })
+    }
+}
+
+enum VersionedMapInfo {
+    V0(MapInfoV0),
+    V1(MapInfoV2),
+    V2(MapInfoV2),
+    V3(MapFile),
+    V4(MapFile),
+    V5(MapFile),
+    V6(MapFile),
+    V7(MapFile),
+    V8(MapFile)
+}
+
+impl VersionedMapInfo {
+    fn parse(bytes: &[u8]) -> Result<Self, MapFormatError> {
+        let probe: FormatVersionProbe = from_bytes(bytes)?;
+        match probe.format_version {
+            0 => Ok(VersionedMapInfo::V0(from_bytes(bytes)?)),
+            1 => Ok(VersionedMapInfo::V1(from_bytes(bytes)?)),
+            2 => Ok(VersionedMapInfo::V2(from_bytes(bytes)?)),
+            3 => Ok(VersionedMapInfo::V3(from_bytes(bytes)?)),
+            4 => Ok(VersionedMapInfo::V4(from_bytes(bytes)?)),
+            5 => Ok(VersionedMapInfo::V5(from_bytes(bytes)?)),
+            6 => Ok(VersionedMapInfo::V6(from_bytes(bytes)?)),
+            7 => Ok(VersionedMapInfo::V7(from_bytes(bytes)?)),
+            8 => Ok(VersionedMapInfo::V8(from_bytes(bytes)?)),
+            found => Err(MapFormatError::UnsupportedVersion {
+                found,
+                supported: CURRENT_FORMAT_VERSION
+            })
+        }
+    }
+
+    fn migrate_to_current(self) -> Result<MapFile, MapFormatError> {
+        let mut map = self;
+        loop {
+            map = match map {
+                VersionedMapInfo::V0(v0) => VersionedMapInfo::V1(v0.migrate()),
+                // Version 2 only added the optional `terrain_blend`.
+                VersionedMapInfo::V1(v1) => VersionedMapInfo::V2(v1),
+                VersionedMapInfo::V2(v2) => VersionedMapInfo::V3(v2.migrate()?),
+                // Version 4 only added `ambient_light`, which version 3 maps read as fully lit.
+                VersionedMapInfo::V3(v3) => VersionedMapInfo::V4(v3),
+                // Version 5 only added `regions`, which version 4 maps have none of.
+                VersionedMapInfo::V4(v4) => VersionedMapInfo::V5(v4),
+                // Version 6 only added `metadata`, which version 5 maps read as the defaults.
+                VersionedMapInfo::V5(v5) => VersionedMapInfo::V6(v5),
+                // Version 7 only added the metadata `seed`, which version 6 maps read as 0.
+                VersionedMapInfo::V6(v6) => VersionedMapInfo::V7(v6),
+                // Version 8 only added the metadata `spawn_weights`, which version 7 maps have none of.
+                VersionedMapInfo::V7(v7) => VersionedMapInfo::V8(v7),
+                VersionedMapInfo::V8(current) => return Ok(current)
+            }
+        }
+    }
+}
+
+/// Parses a map of any known format version and upgrades it step by step
+/// to `CURRENT_FORMAT_VERSION`. Returns the map together with the version it was stored in.
+pub(crate) fn load_and_migrate(bytes: &[u8]) -> Result<(MapFile, u32), MapFormatError> {
+    let versioned = VersionedMapInfo::parse(bytes)?;
+    let original_version = match &versioned {
+        VersionedMapInfo::V0(_) => 0,
+        VersionedMapInfo::V1(_) => 1,
+        VersionedMapInfo::V2(_) => 2,
+        VersionedMapInfo::V3(_) => 3,
+        VersionedMapInfo::V4(_) => 4,
+        VersionedMapInfo::V5(_) => 5,
+        VersionedMapInfo::V6(_) => 6,
+        VersionedMapInfo::V7(_) => 7,
+        VersionedMapInfo::V8(_) => 8
+    };
+    Ok((versioned.migrate_to_current()?, original_version))
+}
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use crate::{Grid, MapEntity, MapInfo, TerrainKind, Unit, UnitDefinitions};
+
+pub type MoveCost = u32;
+
+/// Cost of stepping onto an ordinary tile. Harder tiles cost a multiple of it.
+pub const BASE_MOVE_COST: MoveCost = 10;
+
+/// How a mover gets around, deciding which tiles it can enter and at what cost.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum MovementProfile {
+    Walker,
+    /// Moves freely in water and slowly on land.
+    Swimmer,
+    /// Ignores terrain, but not walls or blocking entities.
+    Flyer,
+    /// Passes through anything, like ghosts do.
+    WallPasser
+}
+
+impl MovementProfile {
+    pub const ALL: [MovementProfile; 4] = [
+        MovementProfile::Walker,
+        MovementProfile::Swimmer,
+        MovementProfile::Flyer,
+        MovementProfile::WallPasser
+    ];
+
+    /// Cost of entering a tile of `terrain`, `None` if the tile can not be entered.
+    pub fn terrain_cost(self, terrain: TerrainKind) -> Option<MoveCost> {
+        let is_water = matches!(terrain, TerrainKind::Water | TerrainKind::CaveWater);
+        match self {
+            MovementProfile::Walker => match terrain {
+                TerrainKind::Water | TerrainKind::Lava => None,
+                TerrainKind::CaveWater => Some(BASE_MOVE_COST * 3),
+                _ => Some(BASE_MOVE_COST)
+            },
+            MovementProfile::Swimmer => match terrain {
+                TerrainKind::Lava => None,
+                _ if is_water => Some(BASE_MOVE_COST),
+                _ => Some(BASE_MOVE_COST * 2)
+            },
+            MovementProfile::Flyer | MovementProfile::WallPasser => Some(BASE_MOVE_COST)
+        }
+    }
+
+    pub fn passes_walls(self) -> bool {
+        matches!(self, MovementProfile::WallPasser)
+    }
+}
+
+impl Unit {
+    /// How the unit moves according to its definition in `units`.
+    pub fn movement_profile(self, units: &UnitDefinitions) -> MovementProfile {
+        units
+            .get(self)
+            .map_or_else(|| self.default_movement_profile(), |definition| definition.movement)
+    }
+
+    /// Movement of units missing from the unit definitions.
+    pub fn default_movement_profile(self) -> MovementProfile {
+        match self {
+            Unit::Bat | Unit::Gorynich => MovementProfile::Flyer,
+            Unit::Vodyanoy | Unit::Rusalka => MovementProfile::Swimmer,
+            Unit::Ghost => MovementProfile::WallPasser,
+            _ => MovementProfile::Walker
+        }
+    }
+}
+
+impl MapInfo {
+    /// Cost of entering every tile for `profile`, `None` for tiles which can not be entered.
+    /// Walls and movement blocking footprints of entities block everyone except wall passers.
+    /// Units are not obstacles here, since they move around during play.
+    pub fn passability(&self, profile: MovementProfile) -> Grid<Option<MoveCost>> {
+        let mut costs = Grid::from_fn(self.width(), self.height(), |pos| {
+            let terrain = *self.terrain_layer.get(pos).unwrap();
+            let wall = *self.wall_layer.get(pos).unwrap();
+            match wall {
+                Some(_) if !profile.passes_walls() => None,
+                _ => profile.terrain_cost(terrain)
+            }
+        });
+
+        if !profile.passes_walls() {
+            for placed in self.entities.iter() {
+                if matches!(placed.entity, MapEntity::Unit(_)) {
+                    continue;
+                }
+                for (pos, cell) in self.footprint_tiles(placed.entity, placed.position()) {
+                    if cell.blocks_movement {
+                        costs.set(pos, None);
+                    }
+                }
+            }
+        }
+
+        costs
+    }
+}
@@ -0,0 +1,242 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap
+};
+use crate::{Grid, MapInfo, MoveCost, MovementProfile, TilePos, BASE_MOVE_COST};
+
+/// Movement costs of a map, as produced by `MapInfo::passability`.
+pub type CostGrid = Grid<Option<MoveCost>>;
+
+const ORTHOGONAL_STEPS: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+const DIAGONAL_STEPS: [(isize, isize); 4] = [(1, -1), (1, 1), (-1, 1), (-1, -1)];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PathOptions {
+    pub profile: MovementProfile,
+    /// Diagonal steps cost 1.4 times the tile cost and never cut the corner of an impassable tile.
+    pub allow_diagonal: bool
+}
+
+impl PathOptions {
+    pub fn new(profile: MovementProfile) -> Self {
+        Self {
+            profile,
+            allow_diagonal: false
+        }
+    }
+
+    pub fn with_diagonal(self, allow_diagonal: bool) -> Self {
+        Self { allow_diagonal, ..self }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TilePath {
+    /// Tiles from the start to the goal, both included.
+    pub tiles: Vec<TilePos>,
+    pub cost: MoveCost
+}
+
+fn step_cost(tile_cost: MoveCost, diagonal: bool) -> MoveCost {
+    if diagonal {
+        tile_cost * 14 / 10
+    } else {
+        tile_cost
+    }
+}
+
+fn is_passable(costs: &CostGrid, pos: TilePos) -> bool {
+    matches!(costs.get(pos), Some(Some(_)))
+}
+
+/// Cost of entering `pos`. Impassable tiles are only entered as the last step towards a
+/// distance map source, which then costs as much as an ordinary tile.
+fn entry_cost(costs: &CostGrid, pos: TilePos) -> MoveCost {
+    costs.get(pos).copied().flatten().unwrap_or(BASE_MOVE_COST)
+}
+
+/// Calls `f` with every neighbour of `pos` in the grid and whether the step to it is diagonal.
+/// Diagonal steps are skipped when either tile they pass by is impassable.
+fn for_each_step(costs: &CostGrid, pos: TilePos, allow_diagonal: bool, mut f: impl FnMut(TilePos, bool)) {
+    for (dx, dy) in ORTHOGONAL_STEPS {
+        if let Some(next) = pos.offset(dx, dy).filter(|&next| costs.contains(next)) {
+            f(next, false);
+        }
+    }
+    if !allow_diagonal {
+        return;
+    }
+    for (dx, dy) in DIAGONAL_STEPS {
+        let corners_open = [pos.offset(dx, 0), pos.offset(0, dy)]
+            .into_iter()
+            .all(|corner| corner.is_some_and(|corner| is_passable(costs, corner)));
+        if !corners_open {
+            continue;
+        }
+        if let Some(next) = pos.offset(dx, dy).filter(|&next| costs.contains(next)) {
+            f(next, true);
+        }
+    }
+}
+
+/// Lower bound of the cost between two tiles, given every tile costs at least `min_cost`.
+fn heuristic(from: TilePos, to: TilePos, allow_diagonal: bool, min_cost: MoveCost) -> MoveCost {
+    let dx = from.x.abs_diff(to.x) as MoveCost;
+    let dy = from.y.abs_diff(to.y) as MoveCost;
+    if allow_diagonal {
+        let diagonal = dx.min(dy);
+        let straight = dx.max(dy) - diagonal;
+        diagonal * step_cost(min_cost, true) + straight * min_cost
+    } else {
+        (dx + dy) * min_cost
+    }
+}
+
+fn trace_back(came_from: &Grid<Option<TilePos>>, goal: TilePos) -> Vec<TilePos> {
+    let mut tiles = vec![goal];
+    let mut current = goal;
+    while let Some(&Some(previous)) = came_from.get(current) {
+        tiles.push(previous);
+        current = previous;
+    }
+    tiles.reverse();
+    tiles
+}
+
+/// Cheapest path from `from` to `to` by A*. The start tile may be impassable, the goal may not.
+/// Ties are broken by tile order, so equal inputs always give the same path.
+/// The estimate of the remaining cost assumes the cheapest tile of `costs` everywhere,
+/// so the path stays the cheapest one for any costs, not only those of `MapInfo::passability`.
+pub fn find_path(costs: &CostGrid, from: TilePos, to: TilePos, allow_diagonal: bool) -> Option<TilePath> {
+    if !costs.contains(from) || !is_passable(costs, to) {
+        return None;
+    }
+    let min_cost = costs.iter().filter_map(|(_, &cost)| cost).min().unwrap_or(BASE_MOVE_COST);
+
+    let mut best = Grid::new(costs.width(), costs.height(), MoveCost::MAX);
+    let mut came_from: Grid<Option<TilePos>> = Grid::new(costs.width(), costs.height(), None);
+    let mut open = BinaryHeap::new();
+    best.set(from, 0);
+    open.push(Reverse((heuristic(from, to, allow_diagonal, min_cost), 0, from)));
+
+    while let Some(Reverse((_, cost, pos))) = open.pop() {
+        if pos == to {
+            return Some(TilePath {
+                tiles: trace_back(&came_from, to),
+                cost
+            });
+        }
+        if cost > *best.get(pos).unwrap() {
+            continue;
+        }
+        for_each_step(costs, pos, allow_diagonal, |next, diagonal| {
+            let tile_cost = match costs.get(next) {
+                Some(&Some(tile_cost)) => tile_cost,
+                _ => return
+            };
+            let next_cost = cost + step_cost(tile_cost, diagonal);
+            if next_cost < *best.get(next).unwrap() {
+                best.set(next, next_cost);
+                came_from.set(next, Some(pos));
+                open.push(Reverse((next_cost + heuristic(next, to, allow_diagonal, min_cost), next_cost, next)));
+            }
+        });
+    }
+    None
+}
+
+/// Cost of reaching the nearest source from every tile, computed by Dijkstra.
+/// With a single source this is a flow map towards it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DistanceMap {
+    costs: CostGrid,
+    distances: Grid<Option<MoveCost>>,
+    allow_diagonal: bool
+}
+
+impl DistanceMap {
+    /// Sources outside of the grid are ignored. Impassable sources are allowed,
+    /// so the map can lead to an entity standing on a blocked tile.
+    pub fn new(costs: CostGrid, sources: &[TilePos], allow_diagonal: bool) -> Self {
+        let mut distances: Grid<Option<MoveCost>> = Grid::new(costs.width(), costs.height(), None);
+        let mut open = BinaryHeap::new();
+        for &source in sources {
+            if distances.set(source, Some(0)) {
+                open.push(Reverse((0, source)));
+            }
+        }
+
+        // The search runs from the sources outwards, so a step costs as much as the tile it
+        // leaves, which is the tile entered when walking towards the sources.
+        while let Some(Reverse((distance, pos))) = open.pop() {
+            if *distances.get(pos).unwrap() != Some(distance) {
+                continue;
+            }
+            for_each_step(&costs, pos, allow_diagonal, |next, diagonal| {
+                if !is_passable(&costs, next) {
+                    return;
+                }
+                let next_distance = distance + step_cost(entry_cost(&costs, pos), diagonal);
+                if distances.get(next).unwrap().is_none_or(|known| next_distance < known) {
+                    distances.set(next, Some(next_distance));
+                    open.push(Reverse((next_distance, next)));
+                }
+            });
+        }
+
+        Self {
+            costs,
+            distances,
+            allow_diagonal
+        }
+    }
+
+    /// `None` for tiles from which no source can be reached.
+    pub fn distance(&self, pos: TilePos) -> Option<MoveCost> {
+        self.distances.get(pos).copied().flatten()
+    }
+
+    pub fn distances(&self) -> &Grid<Option<MoveCost>> {
+        &self.distances
+    }
+
+    /// The step from `pos` along the cheapest way to a source,
+    /// `None` at a source or on an unreachable tile.
+    pub fn next_step(&self, pos: TilePos) -> Option<TilePos> {
+        let distance = self.distance(pos)?;
+        let mut best = None;
+        for_each_step(&self.costs, pos, self.allow_diagonal, |next, diagonal| {
+            let next_distance = match self.distance(next) {
+                Some(next_distance) if next_distance < distance => next_distance,
+                _ => return
+            };
+            let total = next_distance + step_cost(entry_cost(&self.costs, next), diagonal);
+            if best.is_none_or(|best| (total, next) < best) {
+                best = Some((total, next));
+            }
+        });
+        best.map(|(_, next)| next)
+    }
+
+    /// Tiles from `from` down to the nearest source, both included.
+    pub fn path_from(&self, from: TilePos) -> Option<Vec<TilePos>> {
+        self.distance(from)?;
+        let mut tiles = vec![from];
+        let mut current = from;
+        while let Some(next) = self.next_step(current) {
+            tiles.push(next);
+            current = next;
+        }
+        Some(tiles)
+    }
+}
+
+impl MapInfo {
+    pub fn find_path(&self, from: TilePos, to: TilePos, options: PathOptions) -> Option<TilePath> {
+        find_path(&self.passability(options.profile), from, to, options.allow_diagonal)
+    }
+
+    pub fn distance_map(&self, sources: &[TilePos], options: PathOptions) -> DistanceMap {
+        DistanceMap::new(self.passability(options.profile), sources, options.allow_diagonal)
+    }
+}
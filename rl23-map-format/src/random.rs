@@ -0,0 +1,23 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use crate::TerrainKind;
+
+/// Random generator behind every random choice made for a map.
+/// ChaCha keeps its output stable across platforms and `rand` releases, so a seed always gives the same map.
+pub type MapRng = ChaCha8Rng;
+
+pub fn map_rng(seed: u64) -> MapRng {
+    MapRng::seed_from_u64(seed)
+}
+
+impl TerrainKind {
+    /// Number of mud variants in the tileset.
+    pub const MUD_VARIANTS: usize = 12;
+
+    /// Mud with a random variant. The variant is drawn as a `u32`,
+    /// since `rand` draws a `usize` differently on 32 and 64 bit targets.
+    pub fn random_mud(rng: &mut impl Rng) -> Self {
+        let offset = rng.gen_range(0..Self::MUD_VARIANTS as u32);
+        TerrainKind::Mud { offset: offset as usize }
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::BTreeSet;
+use serde::{Deserialize, Serialize};
+use crate::{EntityComponentData, GridBounds, MapInfo, TilePos};
+
+/// Identifier of a region, produced by the map's `IdGenerator` like entity ids.
+pub type RegionId = u64;
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum RegionShape {
+    Rect {
+        min: TilePos,
+        width: usize,
+        height: usize
+    },
+    Tiles(BTreeSet<TilePos>)
+}
+
+impl RegionShape {
+    pub fn contains(&self, pos: TilePos) -> bool {
+        match self {
+            RegionShape::Rect { min, width, height } => {
+                pos.x.checked_sub(min.x).is_some_and(|dx| dx < *width) &&
+                    pos.y.checked_sub(min.y).is_some_and(|dy| dy < *height)
+            }
+            RegionShape::Tiles(tiles) => tiles.contains(&pos)
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            RegionShape::Rect { width, height, .. } => *width == 0 || *height == 0,
+            RegionShape::Tiles(tiles) => tiles.is_empty()
+        }
+    }
+
+    /// Covered tiles in row-major order, including those outside of the map.
+    pub fn tiles(&self) -> Vec<TilePos> {
+        match self {
+            RegionShape::Rect { min, width, height } => (min.y..min.y.saturating_add(*height))
+                .flat_map(|y| (min.x..min.x.saturating_add(*width)).map(move |x| TilePos::new(x, y)))
+                .collect(),
+            RegionShape::Tiles(tiles) => tiles.iter().copied().collect()
+        }
+    }
+
+    /// Covered tiles inside of `bounds` in row-major order.
+    /// Rectangles are cut to `bounds` first, so a huge one costs no more than the map.
+    pub fn tiles_within(&self, bounds: GridBounds) -> Vec<TilePos> {
+        match self {
+            RegionShape::Rect { min, width, height } => {
+                let x_end = min.x.saturating_add(*width).min(bounds.width);
+                let y_end = min.y.saturating_add(*height).min(bounds.height);
+                (min.y..y_end)
+                    .flat_map(|y| (min.x..x_end).map(move |x| TilePos::new(x, y)))
+                    .collect()
+            }
+            RegionShape::Tiles(tiles) => tiles.iter().copied().filter(|&pos| bounds.contains(pos)).collect()
+        }
+    }
+
+    /// Adds a tile, turning a rectangle into the set of its tiles inside of `bounds`
+    /// unless it already covers the tile.
+    pub fn insert(&mut self, pos: TilePos, bounds: GridBounds) {
+        if self.contains(pos) {
+            return;
+        }
+        let mut tiles: BTreeSet<TilePos> = match self {
+            RegionShape::Rect { .. } => self.tiles_within(bounds).into_iter().collect(),
+            RegionShape::Tiles(tiles) => std::mem::take(tiles)
+        };
+        tiles.insert(pos);
+        *self = RegionShape::Tiles(tiles);
+    }
+
+    /// Removes a tile, turning a rectangle into the set of its tiles inside of `bounds`
+    /// if it covers the tile.
+    pub fn remove(&mut self, pos: TilePos, bounds: GridBounds) {
+        if !self.contains(pos) {
+            return;
+        }
+        let mut tiles: BTreeSet<TilePos> = match self {
+            RegionShape::Rect { .. } => self.tiles_within(bounds).into_iter().collect(),
+            RegionShape::Tiles(tiles) => std::mem::take(tiles)
+        };
+        tiles.remove(&pos);
+        *self = RegionShape::Tiles(tiles);
+    }
+
+    /// Shifts the shape by `offset` and cuts off everything outside of `bounds`.
+    pub(crate) fn relocate(&mut self, bounds: GridBounds, offset: (isize, isize)) {
+        *self = match self {
+            RegionShape::Rect { min, width, height } => {
+                let clamp = |start: usize, length: usize, shift: isize, limit: usize| {
+                    let start = isize::try_from(start).unwrap_or(isize::MAX).saturating_add(shift);
+                    let length = isize::try_from(length).unwrap_or(isize::MAX);
+                    let end = start.saturating_add(length).clamp(0, limit as isize);
+                    let start = start.clamp(0, limit as isize);
+                    (start as usize, (end - start).max(0) as usize)
+                };
+                let (x, width) = clamp(min.x, *width, offset.0, bounds.width);
+                let (y, height) = clamp(min.y, *height, offset.1, bounds.height);
+                RegionShape::Rect { min: TilePos::new(x, y), width, height }
+            }
+            RegionShape::Tiles(tiles) => RegionShape::Tiles(
+                tiles
+                    .iter()
+                    .filter_map(|pos| pos.offset(offset.0, offset.1))
+                    .filter(|&pos| bounds.contains(pos))
+                    .collect()
+            )
+        };
+    }
+}
+
+/// Named area of a map, like a village or a zone where nothing spawns.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Region {
+    pub id: RegionId,
+    pub name: String,
+    pub shape: RegionShape,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Exclusive regions must not overlap each other.
+    #[serde(default)]
+    pub exclusive: bool,
+    #[serde(default)]
+    pub components: Vec<EntityComponentData>
+}
+
+impl Region {
+    pub fn new(id: RegionId, name: impl Into<String>, shape: RegionShape) -> Self {
+        Self {
+            id,
+            name: name.into(),
+            shape,
+            tags: Vec::new(),
+            exclusive: false,
+            components: Vec::new()
+        }
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|it| it == tag)
+    }
+
+    pub fn contains(&self, pos: TilePos) -> bool {
+        self.shape.contains(pos)
+    }
+}
+
+impl MapInfo {
+    pub fn region(&self, id: RegionId) -> Option<&Region> {
+        self.regions.iter().find(|region| region.id == id)
+    }
+
+    pub fn region_mut(&mut self, id: RegionId) -> Option<&mut Region> {
+        self.regions.iter_mut().find(|region| region.id == id)
+    }
+
+    /// Regions covering `pos`, in the order they are stored in.
+    pub fn regions_at(&self, pos: TilePos) -> Vec<&Region> {
+        self.regions.iter().filter(|region| region.contains(pos)).collect()
+    }
+
+    /// Tiles of `region` inside of the map, in row-major order.
+    pub fn tiles_in(&self, region: &Region) -> Vec<TilePos> {
+        region.shape.tiles_within(self.bounds())
+    }
+}
@@ -0,0 +1,158 @@
+use std::str::FromStr;
+use rand::Rng;
+use crate::{
+    Grid,
+    MapFormatError,
+    MapInfo,
+    PlacedEntity,
+    SparseGrid,
+    TerrainKind,
+    TilePos
+};
+
+/// The part of the map which keeps its place when the map is resized.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ResizeAnchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight
+}
+
+impl ResizeAnchor {
+    pub const ALL: [ResizeAnchor; 9] = [
+        ResizeAnchor::TopLeft, ResizeAnchor::Top, ResizeAnchor::TopRight,
+        ResizeAnchor::Left, ResizeAnchor::Center, ResizeAnchor::Right,
+        ResizeAnchor::BottomLeft, ResizeAnchor::Bottom, ResizeAnchor::BottomRight
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ResizeAnchor::TopLeft => "top-left",
+            ResizeAnchor::Top => "top",
+            ResizeAnchor::TopRight => "top-right",
+            ResizeAnchor::Left => "left",
+            ResizeAnchor::Center => "center",
+            ResizeAnchor::Right => "right",
+            ResizeAnchor::BottomLeft => "bottom-left",
+            ResizeAnchor::Bottom => "bottom",
+            ResizeAnchor::BottomRight => "bottom-right"
+        }
+    }
+
+    /// Offset that moves old tile positions to their place on the resized map.
+    fn offset(self, old_size: (usize, usize), new_size: (usize, usize)) -> (isize, isize) {
+        let dw = new_size.0 as isize - old_size.0 as isize;
+        let dh = new_size.1 as isize - old_size.1 as isize;
+        let (column, row) = match self {
+            ResizeAnchor::TopLeft => (0, 0),
+            ResizeAnchor::Top => (1, 0),
+            ResizeAnchor::TopRight => (2, 0),
+            ResizeAnchor::Left => (0, 1),
+            ResizeAnchor::Center => (1, 1),
+            ResizeAnchor::Right => (2, 1),
+            ResizeAnchor::BottomLeft => (0, 2),
+            ResizeAnchor::Bottom => (1, 2),
+            ResizeAnchor::BottomRight => (2, 2)
+        };
+        (dw * column / 2, dh * row / 2)
+    }
+}
+
+impl FromStr for ResizeAnchor {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ResizeAnchor::ALL.into_iter().find(|anchor| anchor.name() == s).ok_or(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ResizeReport {
+    /// Entities which were cut off, with their positions on the old map.
+    pub dropped_entities: Vec<PlacedEntity>,
+    pub dropped_gatherables: usize
+}
+
+fn move_sparse_layer<T>(
+    layer: &mut SparseGrid<T>,
+    new_size: (usize, usize),
+    offset: (isize, isize),
+    mut on_drop: impl FnMut(TilePos, T)
+) {
+    let old_layer = std::mem::replace(layer, SparseGrid::new(new_size.0, new_size.1));
+    for (old_pos, value) in old_layer {
+        match old_pos.offset(offset.0, offset.1) {
+            Some(new_pos) if layer.bounds().contains(new_pos) => {
+                layer.insert(new_pos, value);
+            }
+            _ => on_drop(old_pos, value)
+        }
+    }
+}
+
+impl MapInfo {
+    /// Changes map dimensions keeping the `anchor` part of the map in place.
+    /// New tiles are filled with mud picked by `rng` and have no walls.
+    /// Entities and gatherables which fall outside of the new bounds are removed and reported.
+    /// Regions are cut to the new bounds and kept even when nothing of them is left.
+    /// The player spawn point moves with the map and is cleared when it is cut off.
+    /// Sizes without tiles are rejected and leave the map untouched.
+    pub fn resize(
+        &mut self,
+        new_width: usize,
+        new_height: usize,
+        anchor: ResizeAnchor,
+        rng: &mut impl Rng
+    ) -> Result<ResizeReport, MapFormatError> {
+        if new_width == 0 || new_height == 0 {
+            return Err(MapFormatError::EmptyMap {
+                width: new_width,
+                height: new_height
+            });
+        }
+        let old_size = (self.width(), self.height());
+        let new_size = (new_width, new_height);
+        let (dx, dy) = anchor.offset(old_size, new_size);
+
+        let old_position = |pos: TilePos| pos.offset(-dx, -dy);
+
+        let terrain_layer = &self.terrain_layer;
+        self.terrain_layer = Grid::from_fn(new_width, new_height, |pos| {
+            match old_position(pos).and_then(|old_pos| terrain_layer.get(old_pos)) {
+                Some(&terrain) => terrain,
+                None => TerrainKind::random_mud(rng)
+            }
+        });
+        let wall_layer = &self.wall_layer;
+        self.wall_layer = Grid::from_fn(new_width, new_height, |pos| {
+            old_position(pos)
+                .and_then(|old_pos| wall_layer.get(old_pos))
+                .copied()
+                .flatten()
+        });
+
+        let mut report = ResizeReport::default();
+
+        move_sparse_layer(&mut self.gatherable_layer, new_size, (dx, dy), |_, _| {
+            report.dropped_gatherables += 1;
+        });
+
+        report.dropped_entities = self.entities.relocate(new_width, new_height, (dx, dy));
+
+        let bounds = self.bounds();
+        for region in self.regions.iter_mut() {
+            region.shape.relocate(bounds, (dx, dy));
+        }
+        self.metadata.player_spawn = self.metadata.player_spawn
+            .and_then(|pos| pos.offset(dx, dy))
+            .filter(|&pos| bounds.contains(pos));
+
+        Ok(report)
+    }
+}
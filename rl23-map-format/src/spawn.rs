@@ -0,0 +1,208 @@
+use std::{
+    fs::File,
+    io::Read,
+    path::PathBuf
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::{
+    map_rng,
+    Biome,
+    EntityComponentData,
+    EntityId,
+    MapFormatError,
+    MapInfo,
+    SpawnRandomUnit,
+    TilePos,
+    Unit,
+    UnitDefinitions
+};
+
+/// Spawn pool shipped with the game.
+pub const DEFAULT_SPAWN_POOL: &str = include_str!("../../assets/spawn_pool.ron");
+
+/// Region tag of areas where nothing spawns, like villages.
+pub const NO_SPAWN_TAG: &str = "no_spawn";
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct GroupMember {
+    pub unit: Unit,
+    pub min_count: u32,
+    pub max_count: u32
+}
+
+/// A unit or a group of units spawning together, like a wolf pack.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SpawnPoolEntry {
+    pub name: String,
+    pub members: Vec<GroupMember>,
+    /// Chance relative to the other entries, maps may override it by entry name.
+    pub weight: u32,
+    /// Biomes the entry spawns in, every biome when empty.
+    #[serde(default)]
+    pub biomes: Vec<Biome>,
+    /// The entry spawns only inside of regions with one of these tags, anywhere when empty.
+    #[serde(default)]
+    pub region_tags: Vec<String>
+}
+
+impl SpawnPoolEntry {
+    /// Level of the strongest unit the entry may spawn, `None` when a unit has no definition.
+    pub fn level(&self, units: &UnitDefinitions) -> Option<i32> {
+        let mut level = None;
+        for member in self.members.iter().filter(|member| member.max_count > 0) {
+            let member_level = units.get(member.unit)?.level;
+            level = Some(level.map_or(member_level, |level: i32| level.max(member_level)));
+        }
+        level
+    }
+}
+
+/// Every unit and group spawners may pick from.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct SpawnPool {
+    pub entries: Vec<SpawnPoolEntry>
+}
+
+impl Default for SpawnPool {
+    fn default() -> Self {
+        ron::from_str(DEFAULT_SPAWN_POOL).expect("embedded spawn pool must be valid")
+    }
+}
+
+impl SpawnPool {
+    pub fn read_from_path(path: &PathBuf) -> Result<Self, MapFormatError> {
+        let mut bytes = Vec::new();
+        let mut file = File::open(path)?;
+        file.read_to_end(&mut bytes)?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+}
+
+/// `SpawnRandomUnit` component of an entity.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Spawner {
+    pub id: EntityId,
+    pub position: TilePos,
+    pub spawn: SpawnRandomUnit
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpawnCandidate<'a> {
+    pub entry: &'a SpawnPoolEntry,
+    /// Weight after the overrides of the map.
+    pub weight: u32
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpawnChoice {
+    /// Name of the picked pool entry.
+    pub entry: String,
+    pub units: Vec<Unit>
+}
+
+impl MapInfo {
+    /// Every spawner of the map, ordered by entity id.
+    pub fn spawners(&self) -> Vec<Spawner> {
+        self.entities
+            .iter()
+            .flat_map(|placed| {
+                placed.components.iter().filter_map(move |component| match component {
+                    EntityComponentData::SpawnRandomUnit(spawn) => Some(Spawner {
+                        id: placed.id(),
+                        position: placed.position(),
+                        spawn: *spawn
+                    }),
+                    _ => None
+                })
+            })
+            .collect()
+    }
+}
+
+/// Turns spawners of a map into concrete units.
+pub struct SpawnResolver<'a> {
+    map_info: &'a MapInfo,
+    pool: &'a SpawnPool,
+    units: &'a UnitDefinitions
+}
+
+impl<'a> SpawnResolver<'a> {
+    pub fn new(map_info: &'a MapInfo, pool: &'a SpawnPool, units: &'a UnitDefinitions) -> Self {
+        Self { map_info, pool, units }
+    }
+
+    /// Pool entries a spawner may pick, in pool order, with their weights for this map.
+    /// Entries without definitions for all of their units never spawn.
+    pub fn candidates(&self, spawner: &Spawner) -> Vec<SpawnCandidate<'a>> {
+        let regions = self.map_info.regions_at(spawner.position);
+        if regions.iter().any(|region| region.has_tag(NO_SPAWN_TAG)) {
+            return Vec::new();
+        }
+        let metadata = &self.map_info.metadata;
+        self.pool
+            .entries
+            .iter()
+            .filter(|entry| {
+                entry.level(self.units).is_some_and(|level| {
+                    (spawner.spawn.min_level..=spawner.spawn.max_level).contains(&level)
+                })
+            })
+            .filter(|entry| entry.biomes.is_empty() || entry.biomes.contains(&metadata.biome))
+            .filter(|entry| {
+                entry.region_tags.is_empty() || regions
+                    .iter()
+                    .any(|region| entry.region_tags.iter().any(|tag| region.has_tag(tag)))
+            })
+            .filter_map(|entry| {
+                let weight = metadata.spawn_weights.get(&entry.name).copied().unwrap_or(entry.weight);
+                (weight > 0).then_some(SpawnCandidate { entry, weight })
+            })
+            .collect()
+    }
+
+    /// Picks an entry by weight and rolls the size of its group.
+    pub fn resolve(&self, spawner: &Spawner, rng: &mut impl Rng) -> Option<SpawnChoice> {
+        let candidates = self.candidates(spawner);
+        let total_weight: u64 = candidates.iter().map(|candidate| candidate.weight as u64).sum();
+        if total_weight == 0 {
+            return None;
+        }
+        let mut pick = rng.gen_range(0..total_weight);
+        let mut entry = candidates[0].entry;
+        for candidate in candidates.iter() {
+            if pick < candidate.weight as u64 {
+                entry = candidate.entry;
+                break;
+            }
+            pick -= candidate.weight as u64;
+        }
+        let mut units = Vec::new();
+        for member in entry.members.iter() {
+            let count = rng.gen_range(member.min_count..=member.max_count.max(member.min_count));
+            units.extend((0..count).map(|_| member.unit));
+        }
+        Some(SpawnChoice { entry: entry.name.clone(), units })
+    }
+
+    /// Resolves every spawner of the map. Each entity rolls from its own stream of `seed`,
+    /// so editing one spawner does not change what the others spawn.
+    pub fn resolve_all(&self, seed: u64) -> Vec<(Spawner, Option<SpawnChoice>)> {
+        let mut rng = map_rng(seed);
+        let mut stream = None;
+        self.map_info
+            .spawners()
+            .into_iter()
+            .map(|spawner| {
+                if stream != Some(spawner.id) {
+                    rng = map_rng(seed);
+                    rng.set_stream(spawner.id);
+                    stream = Some(spawner.id);
+                }
+                let choice = self.resolve(&spawner, &mut rng);
+                (spawner, choice)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,78 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::Read,
+    path::PathBuf
+};
+use serde::{Deserialize, Serialize};
+use crate::{
+    Autotile,
+    EntityDrawCommand,
+    GatherableItem,
+    MapEntity,
+    MapFormatError,
+    TerrainBlend,
+    TerrainKind,
+    WallKind
+};
+
+/// Manifest shipped with the game, describing the sprites of `tiles.im256`.
+pub const DEFAULT_TILESET: &str = include_str!("../../assets/tileset.ron");
+
+/// Positions of every sprite in the tile atlas, so artists can move sprites without code changes.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Tileset {
+    pub mud_variants: Vec<[usize; 2]>,
+    pub terrain: HashMap<TerrainKind, Autotile>,
+    pub walls: HashMap<WallKind, Autotile>,
+    pub entities: HashMap<MapEntity, EntityDrawCommand>,
+    pub mushrooms: Vec<[usize; 2]>,
+    pub wheat: [usize; 2],
+    #[serde(default)]
+    pub terrain_blend: TerrainBlend
+}
+
+impl Default for Tileset {
+    fn default() -> Self {
+        ron::from_str(DEFAULT_TILESET).expect("embedded tileset manifest must be valid")
+    }
+}
+
+impl Tileset {
+    pub fn read_from_path(path: &PathBuf) -> Result<Self, MapFormatError> {
+        let mut bytes = Vec::new();
+        let mut file = File::open(path)?;
+        file.read_to_end(&mut bytes)?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    pub fn mud_coords(&self, offset: usize) -> Option<[usize; 2]> {
+        if self.mud_variants.is_empty() {
+            return None;
+        }
+        Some(self.mud_variants[offset % self.mud_variants.len()])
+    }
+
+    /// Autotiling of a terrain kind. `Mud` is drawn with `mud_coords` instead.
+    pub fn terrain(&self, kind: TerrainKind) -> Option<Autotile> {
+        self.terrain.get(&kind).copied()
+    }
+
+    pub fn wall(&self, kind: WallKind) -> Option<Autotile> {
+        self.walls.get(&kind).copied()
+    }
+
+    pub fn entity(&self, map_entity: MapEntity) -> Option<EntityDrawCommand> {
+        self.entities.get(&map_entity).copied()
+    }
+
+    pub fn gatherable_coords(&self, item: GatherableItem) -> Option<[usize; 2]> {
+        match item {
+            GatherableItem::Mushroom(num) if !self.mushrooms.is_empty() => {
+                Some(self.mushrooms[num % self.mushrooms.len()])
+            }
+            GatherableItem::Mushroom(_) => None,
+            GatherableItem::Wheat => Some(self.wheat)
+        }
+    }
+}
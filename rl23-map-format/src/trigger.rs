@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use crate::{EntityComponent, EntityId, RegionId, TilePos};
+
+/// Where the player has to step for an `OnEnterTrigger` to fire.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TriggerArea {
+    /// Tiles within the radius around the entity.
+    Radius(u8),
+    Region(RegionId)
+}
+
+impl Default for TriggerArea {
+    fn default() -> Self {
+        TriggerArea::Radius(1)
+    }
+}
+
+/// Runs the other components of the entity when the player enters `area`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize, EntityComponent)]
+#[component(label = "On Enter", applies_to(Logic))]
+pub struct OnEnterTrigger {
+    pub area: TriggerArea,
+    /// Fires only the first time.
+    pub once: bool
+}
+
+/// Runs the other components of the entity when the player interacts with it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize, EntityComponent)]
+#[component(label = "On Interact", applies_to(Logic, Door, ClosedDoor, Decor, Unit, Tree))]
+pub struct OnInteract {
+    pub once: bool
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize, EntityComponent)]
+#[component(applies_to(Logic, Unit))]
+pub struct StartDialogue {
+    /// Unit the player talks to.
+    #[component(pick_entity)]
+    pub npc_id: EntityId,
+    pub stage: u32
+}
+
+/// Sets a story flag, stored in the save game.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize, EntityComponent)]
+#[component(applies_to(Logic))]
+pub struct SetFlag {
+    pub flag: String,
+    pub value: bool
+}
+
+/// Stops the components after it unless a story flag has the value.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize, EntityComponent)]
+#[component(applies_to(Logic, Door, ClosedDoor))]
+pub struct RequireFlag {
+    pub flag: String,
+    pub value: bool
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize, EntityComponent)]
+#[component(applies_to(Logic, Door))]
+pub struct Teleport {
+    /// File name of the target map, the map itself when empty.
+    pub map: String,
+    pub pos: TilePos
+}
+
+/// Fires the `OnInteract` components of another entity, like a lever opening a door.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize, EntityComponent)]
+#[component(applies_to(Logic))]
+pub struct Activate {
+    #[component(pick_entity)]
+    pub target_id: EntityId
+}
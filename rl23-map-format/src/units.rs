@@ -0,0 +1,126 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::Read,
+    path::PathBuf
+};
+use serde::{Deserialize, Serialize};
+use crate::{MapFormatError, MovementProfile, Unit};
+
+/// Unit definitions shipped with the game.
+pub const DEFAULT_UNIT_DEFINITIONS: &str = include_str!("../../assets/units.ron");
+
+/// Language texts fall back to when they have no translation.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Text in several languages, keyed by language code.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct LocalizedText(pub BTreeMap<String, String>);
+
+impl LocalizedText {
+    /// The text in `language`, in `DEFAULT_LANGUAGE` when it is not translated.
+    pub fn get(&self, language: &str) -> &str {
+        self.0
+            .get(language)
+            .or_else(|| self.0.get(DEFAULT_LANGUAGE))
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Faction {
+    Wildlife,
+    /// The leshy and the spirits of woods and fields.
+    Forest,
+    /// Chorts, water spirits and other unclean force.
+    Unclean,
+    Undead,
+    Bandits,
+    DarkHost,
+    Villagers,
+    Sorcerers
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum UnitTag {
+    Undead,
+    Beast,
+    Spirit,
+    Human
+}
+
+/// How a unit behaves in combat.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum AiArchetype {
+    /// Never attacks, flees when hurt.
+    Passive,
+    /// Keeps its distance and runs once enemies come close.
+    Skittish,
+    Melee,
+    Ranged,
+    Caster,
+    /// Waits unseen until enemies come close.
+    Ambusher,
+    /// Stays near its post and attacks whoever comes close.
+    Guardian
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct UnitDefinition {
+    pub name: LocalizedText,
+    pub hp: u32,
+    pub attack: u32,
+    pub defence: u32,
+    pub speed: u32,
+    pub level: i32,
+    pub faction: Faction,
+    #[serde(default)]
+    pub tags: Vec<UnitTag>,
+    pub movement: MovementProfile,
+    pub ai: AiArchetype
+}
+
+impl UnitDefinition {
+    pub fn has_tag(&self, tag: UnitTag) -> bool {
+        self.tags.contains(&tag)
+    }
+}
+
+/// Stats of every kind of unit, so they can be balanced without code changes.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct UnitDefinitions {
+    pub units: BTreeMap<Unit, UnitDefinition>
+}
+
+impl Default for UnitDefinitions {
+    fn default() -> Self {
+        ron::from_str(DEFAULT_UNIT_DEFINITIONS).expect("embedded unit definitions must be valid")
+    }
+}
+
+impl UnitDefinitions {
+    pub fn read_from_path(path: &PathBuf) -> Result<Self, MapFormatError> {
+        let mut bytes = Vec::new();
+        let mut file = File::open(path)?;
+        file.read_to_end(&mut bytes)?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    pub fn get(&self, unit: Unit) -> Option<&UnitDefinition> {
+        self.units.get(&unit)
+    }
+
+    /// Units without a definition, in declaration order.
+    pub fn missing(&self) -> Vec<Unit> {
+        Unit::ALL.into_iter().filter(|unit| !self.units.contains_key(unit)).collect()
+    }
+}
+
+impl Unit {
+    pub fn definition(self, definitions: &UnitDefinitions) -> Option<&UnitDefinition> {
+        definitions.get(self)
+    }
+}
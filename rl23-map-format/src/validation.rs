@@ -0,0 +1,263 @@
+use std::fmt::{Display, Formatter};
+use crate::{
+    EntityComponentData,
+    MapInfo,
+    MovementProfile,
+    OnEnterTrigger,
+    PlacementConflict,
+    TilePos,
+    TriggerArea
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationIssueKind {
+    IdNotGenerated { id: u64, next_id: u64 },
+    InvalidLevelRange { min_level: i32, max_level: i32 },
+    FootprintOutOfBounds { id: u64 },
+    OverlappingEntities { id: u64, other: u64 },
+    EmptyRegion { id: u64 },
+    OverlappingRegions { id: u64, other: u64 },
+    PlayerSpawnOutOfBounds,
+    PlayerSpawnBlocked,
+    InvalidQuantityRange { min_quantity: u32, max_quantity: u32 },
+    MissingEntity { target: u64 },
+    MissingRegion { region: u64 },
+    TeleportOutOfBounds { target: TilePos }
+}
+
+impl Display for ValidationIssueKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationIssueKind::IdNotGenerated { id, next_id } => write!(
+                f,
+                "id {} was not produced by the id generator (next id is {})",
+                id, next_id
+            ),
+            ValidationIssueKind::InvalidLevelRange { min_level, max_level } => write!(
+                f,
+                "min_level {} is greater than max_level {}",
+                min_level, max_level
+            ),
+            ValidationIssueKind::FootprintOutOfBounds { id } => {
+                write!(f, "entity {} does not fit into the map", id)
+            }
+            ValidationIssueKind::OverlappingEntities { id, other } => {
+                write!(f, "entity {} overlaps entity {}", id, other)
+            }
+            ValidationIssueKind::EmptyRegion { id } => {
+                write!(f, "region {} covers no tiles of the map", id)
+            }
+            ValidationIssueKind::OverlappingRegions { id, other } => {
+                write!(f, "exclusive region {} overlaps exclusive region {}", id, other)
+            }
+            ValidationIssueKind::PlayerSpawnOutOfBounds => {
+                write!(f, "player spawn point is outside of the map")
+            }
+            ValidationIssueKind::PlayerSpawnBlocked => {
+                write!(f, "player spawn point can not be walked on")
+            }
+            ValidationIssueKind::InvalidQuantityRange { min_quantity, max_quantity } => write!(
+                f,
+                "min_quantity {} is greater than max_quantity {}",
+                min_quantity, max_quantity
+            ),
+            ValidationIssueKind::MissingEntity { target } => {
+                write!(f, "referenced entity {} does not exist", target)
+            }
+            ValidationIssueKind::MissingRegion { region } => {
+                write!(f, "referenced region {} does not exist", region)
+            }
+            ValidationIssueKind::TeleportOutOfBounds { target } => {
+                write!(f, "teleport target ({}, {}) is outside of the map", target.x, target.y)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    /// Tile the issue refers to, if it refers to a particular tile.
+    pub position: Option<TilePos>,
+    pub kind: ValidationIssueKind
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error"
+        };
+        match self.position {
+            Some(TilePos { x, y }) => write!(f, "{} at ({}, {}): {}", severity, x, y, self.kind),
+            None => write!(f, "{}: {}", severity, self.kind)
+        }
+    }
+}
+
+fn check_components(
+    map_info: &MapInfo,
+    components: &[EntityComponentData],
+    position: Option<TilePos>,
+    issues: &mut Vec<ValidationIssue>
+) {
+    let missing_entity = |id| map_info.entities.get(id).is_none();
+    let error = |kind| ValidationIssue {
+        severity: Severity::Error,
+        position,
+        kind
+    };
+    for component in components {
+        let kind = match component {
+            EntityComponentData::SpawnRandomUnit(spawn) if spawn.min_level > spawn.max_level => {
+                ValidationIssueKind::InvalidLevelRange {
+                    min_level: spawn.min_level,
+                    max_level: spawn.max_level
+                }
+            }
+            EntityComponentData::OnEnterTrigger(OnEnterTrigger { area: TriggerArea::Region(region), .. })
+                if map_info.region(*region).is_none() =>
+            {
+                ValidationIssueKind::MissingRegion { region: *region }
+            }
+            EntityComponentData::StartDialogue(dialogue) if missing_entity(dialogue.npc_id) => {
+                ValidationIssueKind::MissingEntity { target: dialogue.npc_id }
+            }
+            EntityComponentData::Activate(activate) if missing_entity(activate.target_id) => {
+                ValidationIssueKind::MissingEntity { target: activate.target_id }
+            }
+            // Targets on other maps can only be checked together with those maps.
+            EntityComponentData::Teleport(teleport)
+                if teleport.map.is_empty() && !map_info.bounds().contains(teleport.pos) =>
+            {
+                ValidationIssueKind::TeleportOutOfBounds { target: teleport.pos }
+            }
+            EntityComponentData::LootTable(table) => {
+                for entry in table.guaranteed.iter().chain(table.entries.iter()) {
+                    if entry.min_quantity > entry.max_quantity {
+                        issues.push(error(ValidationIssueKind::InvalidQuantityRange {
+                            min_quantity: entry.min_quantity,
+                            max_quantity: entry.max_quantity
+                        }));
+                    }
+                }
+                continue;
+            }
+            _ => continue
+        };
+        issues.push(error(kind));
+    }
+}
+
+impl MapInfo {
+    /// Checks the map for inconsistencies between its layers.
+    /// Issues of entities come first, then those of regions, each ordered by id,
+    /// then those of the metadata, so repeated runs give the same report.
+    /// Layer sizes, out of bounds entries and duplicate ids are checked when the map is loaded.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        for entity in self.entities.iter() {
+            let pos = entity.position();
+
+            if entity.id() >= self.id_generator.next_id {
+                issues.push(ValidationIssue {
+                    severity: Severity::Error,
+                    position: Some(pos),
+                    kind: ValidationIssueKind::IdNotGenerated {
+                        id: entity.id(),
+                        next_id: self.id_generator.next_id
+                    }
+                });
+            }
+
+            for conflict in self.placement_conflicts(entity.entity, pos, Some(entity.id())) {
+                let kind = match conflict {
+                    PlacementConflict::OutOfBounds => ValidationIssueKind::FootprintOutOfBounds {
+                        id: entity.id()
+                    },
+                    // Every overlapping pair is reported once, by the entity with the smaller id.
+                    PlacementConflict::Overlaps { id, .. } if id < entity.id() => continue,
+                    PlacementConflict::Overlaps { id, .. } => ValidationIssueKind::OverlappingEntities {
+                        id: entity.id(),
+                        other: id
+                    }
+                };
+                issues.push(ValidationIssue {
+                    severity: Severity::Warning,
+                    position: Some(pos),
+                    kind
+                });
+            }
+
+            check_components(self, &entity.components, Some(pos), &mut issues);
+        }
+
+        let mut regions: Vec<_> = self.regions.iter().collect();
+        regions.sort_by_key(|region| region.id);
+        for (idx, region) in regions.iter().enumerate() {
+            let tiles = self.tiles_in(region);
+            let first_tile = tiles.first().copied();
+
+            if region.id >= self.id_generator.next_id {
+                issues.push(ValidationIssue {
+                    severity: Severity::Error,
+                    position: first_tile,
+                    kind: ValidationIssueKind::IdNotGenerated {
+                        id: region.id,
+                        next_id: self.id_generator.next_id
+                    }
+                });
+            }
+
+            if tiles.is_empty() {
+                issues.push(ValidationIssue {
+                    severity: Severity::Warning,
+                    position: None,
+                    kind: ValidationIssueKind::EmptyRegion { id: region.id }
+                });
+            }
+
+            if region.exclusive {
+                for other in regions[idx + 1..].iter().filter(|other| other.exclusive) {
+                    if let Some(&shared) = tiles.iter().find(|&&pos| other.contains(pos)) {
+                        issues.push(ValidationIssue {
+                            severity: Severity::Warning,
+                            position: Some(shared),
+                            kind: ValidationIssueKind::OverlappingRegions {
+                                id: region.id,
+                                other: other.id
+                            }
+                        });
+                    }
+                }
+            }
+
+            check_components(self, &region.components, first_tile, &mut issues);
+        }
+
+        if let Some(spawn) = self.metadata.player_spawn {
+            match self.passability(MovementProfile::Walker).get(spawn) {
+                None => issues.push(ValidationIssue {
+                    severity: Severity::Error,
+                    position: None,
+                    kind: ValidationIssueKind::PlayerSpawnOutOfBounds
+                }),
+                Some(None) => issues.push(ValidationIssue {
+                    severity: Severity::Warning,
+                    position: Some(spawn),
+                    kind: ValidationIssueKind::PlayerSpawnBlocked
+                }),
+                Some(Some(_)) => {}
+            }
+        }
+
+        issues
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::BTreeSet;
+use crate::{Grid, MapEntity, MapInfo, TilePos, Tileset};
+
+impl MapEntity {
+    /// Tall static entities hide whatever is behind them, tall meaning that their sprite in `tileset`
+    /// covers tiles above them. Units are tall too at times, but they move around during play
+    /// and are never treated as obstacles.
+    pub fn blocks_sight(self, tileset: &Tileset) -> bool {
+        !matches!(self, MapEntity::Unit(_)) &&
+            tileset.entity(self).is_some_and(|command| command.blocks_tiles_above > 0)
+    }
+}
+
+/// Slope `num / den` of a line from the origin, `den` is always positive.
+#[derive(Copy, Clone, Debug)]
+struct Slope {
+    num: i64,
+    den: i64
+}
+
+impl Slope {
+    const fn new(num: i64, den: i64) -> Self {
+        Self { num, den }
+    }
+
+    /// Slope of the edge of the tile at `col` closest to the start of a row at `depth`.
+    fn of_tile(depth: i64, col: i64) -> Self {
+        Self::new(2 * col - 1, 2 * depth)
+    }
+
+    /// `depth * self` rounded to the nearest column, ties going up.
+    fn first_col(self, depth: i64) -> i64 {
+        (2 * depth * self.num + self.den).div_euclid(2 * self.den)
+    }
+
+    /// `depth * self` rounded to the nearest column, ties going down.
+    fn last_col(self, depth: i64) -> i64 {
+        -(self.den - 2 * depth * self.num).div_euclid(2 * self.den)
+    }
+
+    /// Whether `col` lies at or after `depth * self`.
+    fn col_is_after(self, depth: i64, col: i64) -> bool {
+        col * self.den >= depth * self.num
+    }
+
+    /// Whether `col` lies at or before `depth * self`.
+    fn col_is_before(self, depth: i64, col: i64) -> bool {
+        col * self.den <= depth * self.num
+    }
+}
+
+struct Row {
+    depth: i64,
+    start: Slope,
+    end: Slope
+}
+
+impl Row {
+    fn next(&self) -> Self {
+        Self {
+            depth: self.depth + 1,
+            ..*self
+        }
+    }
+
+    fn is_symmetric(&self, col: i64) -> bool {
+        self.start.col_is_after(self.depth, col) && self.end.col_is_before(self.depth, col)
+    }
+}
+
+/// Turns `(depth, col)` of one of the four quadrants around `origin` into map coordinates.
+fn transform(quadrant: u8, origin: TilePos, depth: i64, col: i64) -> (i64, i64) {
+    let (x, y) = (origin.x as i64, origin.y as i64);
+    match quadrant {
+        0 => (x + col, y - depth),
+        1 => (x + depth, y + col),
+        2 => (x + col, y + depth),
+        _ => (x - depth, y + col)
+    }
+}
+
+fn tile_at(opaque: &Grid<bool>, (x, y): (i64, i64)) -> Option<TilePos> {
+    if x < 0 || y < 0 {
+        return None;
+    }
+    Some(TilePos::new(x as usize, y as usize)).filter(|&pos| opaque.contains(pos))
+}
+
+/// Tiles seen from `origin` within a circle of `radius` tiles, by symmetric shadowcasting:
+/// a tile sees another exactly when the other sees it. Opaque tiles are seen but hide
+/// what lies behind them, the area outside of the grid counts as opaque.
+pub fn field_of_view(opaque: &Grid<bool>, origin: TilePos, radius: usize) -> BTreeSet<TilePos> {
+    let mut visible = BTreeSet::new();
+    if !opaque.contains(origin) {
+        return visible;
+    }
+    visible.insert(origin);
+
+    let radius = radius as i64;
+    let is_opaque = |pos: Option<TilePos>| pos.is_none_or(|pos| *opaque.get(pos).unwrap());
+
+    for quadrant in 0..4 {
+        let mut rows = vec![Row { depth: 1, start: Slope::new(-1, 1), end: Slope::new(1, 1) }];
+        while let Some(mut row) = rows.pop() {
+            if row.depth > radius {
+                continue;
+            }
+            let mut previous_is_opaque = None;
+            for col in row.start.first_col(row.depth)..=row.end.last_col(row.depth) {
+                let (x, y) = transform(quadrant, origin, row.depth, col);
+                let pos = tile_at(opaque, (x, y));
+                let tile_is_opaque = is_opaque(pos);
+                let in_radius = row.depth * row.depth + col * col <= radius * (radius + 1);
+
+                if let Some(pos) = pos {
+                    if in_radius && (tile_is_opaque || row.is_symmetric(col)) {
+                        visible.insert(pos);
+                    }
+                }
+                if previous_is_opaque == Some(true) && !tile_is_opaque {
+                    row.start = Slope::of_tile(row.depth, col);
+                }
+                if previous_is_opaque == Some(false) && tile_is_opaque {
+                    rows.push(Row {
+                        end: Slope::of_tile(row.depth, col),
+                        ..row.next()
+                    });
+                }
+                previous_is_opaque = Some(tile_is_opaque);
+            }
+            if previous_is_opaque == Some(false) {
+                rows.push(row.next());
+            }
+        }
+    }
+    visible
+}
+
+/// Tiles of the Bresenham line from `from` to `to`, both included.
+pub fn bresenham_line(from: TilePos, to: TilePos) -> Vec<TilePos> {
+    let (mut x, mut y) = (from.x as i64, from.y as i64);
+    let (to_x, to_y) = (to.x as i64, to.y as i64);
+    let dx = (to_x - x).abs();
+    let dy = -(to_y - y).abs();
+    let step_x = if x < to_x { 1 } else { -1 };
+    let step_y = if y < to_y { 1 } else { -1 };
+    let mut error = dx + dy;
+
+    let mut tiles = Vec::with_capacity((dx - dy) as usize + 1);
+    loop {
+        tiles.push(TilePos::new(x as usize, y as usize));
+        if x == to_x && y == to_y {
+            return tiles;
+        }
+        let doubled_error = 2 * error;
+        if doubled_error >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled_error <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+/// Whether nothing opaque lies on the Bresenham line between `from` and `to`.
+/// The end points themselves may be opaque, so a wall can be shot at.
+pub fn line_of_sight(opaque: &Grid<bool>, from: TilePos, to: TilePos) -> bool {
+    if !opaque.contains(from) || !opaque.contains(to) {
+        return false;
+    }
+    let line = bresenham_line(from, to);
+    line[1..line.len().saturating_sub(1)]
+        .iter()
+        .all(|&pos| !*opaque.get(pos).unwrap())
+}
+
+impl MapInfo {
+    /// Tiles hiding what lies behind them: walls and entities which block sight.
+    pub fn sight_blockers(&self, tileset: &Tileset) -> Grid<bool> {
+        let mut opaque = Grid::from_fn(self.width(), self.height(), |pos| {
+            self.wall_layer.get(pos).unwrap().is_some()
+        });
+        for placed in self.entities.iter() {
+            if placed.entity.blocks_sight(tileset) {
+                opaque.set(placed.position(), true);
+            }
+        }
+        opaque
+    }
+
+    pub fn visible_tiles(&self, tileset: &Tileset, origin: TilePos, radius: usize) -> BTreeSet<TilePos> {
+        field_of_view(&self.sight_blockers(tileset), origin, radius)
+    }
+
+    pub fn has_line_of_sight(&self, tileset: &Tileset, from: TilePos, to: TilePos) -> bool {
+        line_of_sight(&self.sight_blockers(tileset), from, to)
+    }
+}
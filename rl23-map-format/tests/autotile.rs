@@ -0,0 +1,183 @@
+use std::collections::{HashMap, HashSet};
+use rl23_map_format::{
+    Autotile,
+    BlobMask,
+    Grid,
+    TilePos,
+    WangEncoding,
+    BLOB_ATLAS_COLUMNS,
+    BLOB_TILE_COUNT
+};
+
+const WANG: Autotile = Autotile::Wang16 { x_offset: 1000, y_offset: 2000 };
+const BLOB: Autotile = Autotile::Blob47 { x_offset: 1000, y_offset: 2000 };
+
+/// Reference normalization, written independently from `BlobMask::normalized`.
+fn keeps_corner(mask: u8, corner: u8) -> bool {
+    let (first, second) = match corner {
+        BlobMask::NORTH_EAST => (BlobMask::NORTH, BlobMask::EAST),
+        BlobMask::SOUTH_EAST => (BlobMask::SOUTH, BlobMask::EAST),
+        BlobMask::SOUTH_WEST => (BlobMask::SOUTH, BlobMask::WEST),
+        BlobMask::NORTH_WEST => (BlobMask::NORTH, BlobMask::WEST),
+        _ => unreachable!()
+    };
+    mask & corner != 0 && mask & first != 0 && mask & second != 0
+}
+
+fn grid_from_rows(rows: &[&str]) -> Grid<bool> {
+    let cells = rows.iter().flat_map(|row| row.chars().map(|c| c == '#')).collect();
+    Grid::from_vec(rows[0].len(), rows.len(), cells).unwrap()
+}
+
+#[test]
+fn every_wang_corner_pattern_maps_to_an_atlas_cell() {
+    // Indexed by NE | NW << 1 | SE << 2 | SW << 3, offsets relative to the terrain origin.
+    let expected: [Option<[usize; 2]>; 16] = [
+        None,
+        Some([64, 32]),
+        Some([96, 32]),
+        Some([0, 0]),
+        Some([64, 0]),
+        Some([32, 32]),
+        Some([32, 0]),
+        Some([96, 64]),
+        Some([96, 0]),
+        Some([32, 64]),
+        Some([32, 96]),
+        Some([64, 64]),
+        Some([0, 64]),
+        Some([96, 96]),
+        Some([64, 96]),
+        Some([0, 96])
+    ];
+
+    let mut seen = HashSet::new();
+    for bits in 0..16u8 {
+        let encoding = WangEncoding::from_bits(bits);
+        assert_eq!(encoding.bits(), bits);
+        let coords = WANG.wang_coords(encoding);
+        assert_eq!(
+            coords,
+            expected[bits as usize].map(|[x, y]| [1000 + x, 2000 + y]),
+            "corner pattern {:04b}", bits
+        );
+        if let Some(coords) = coords {
+            assert!(seen.insert(coords), "corner pattern {:04b} reuses a cell", bits);
+        }
+        assert_eq!(BLOB.wang_coords(encoding), None);
+    }
+    assert_eq!(seen.len(), 15);
+}
+
+#[test]
+fn every_blob_neighbourhood_maps_to_an_atlas_cell() {
+    let mut cells_by_shape = HashMap::new();
+    for bits in 0..=255u8 {
+        let mask = BlobMask(bits);
+        let normalized = mask.normalized().0;
+
+        let edges = BlobMask::NORTH | BlobMask::EAST | BlobMask::SOUTH | BlobMask::WEST;
+        let mut expected = bits & edges;
+        for corner in [
+            BlobMask::NORTH_EAST,
+            BlobMask::SOUTH_EAST,
+            BlobMask::SOUTH_WEST,
+            BlobMask::NORTH_WEST
+        ] {
+            if keeps_corner(bits, corner) {
+                expected |= corner;
+            }
+        }
+        assert_eq!(normalized, expected, "neighbourhood {:08b}", bits);
+
+        let idx = mask.blob_index();
+        assert!(idx < BLOB_TILE_COUNT);
+        assert_eq!(BlobMask(normalized).blob_index(), idx);
+        assert_eq!(
+            BLOB.blob_coords(mask),
+            Some([
+                1000 + (idx % BLOB_ATLAS_COLUMNS) * 32,
+                2000 + (idx / BLOB_ATLAS_COLUMNS) * 32
+            ])
+        );
+        assert_eq!(WANG.blob_coords(mask), None);
+
+        let previous = cells_by_shape.insert(normalized, idx);
+        assert!(previous.is_none() || previous == Some(idx));
+    }
+
+    let cells: HashSet<usize> = cells_by_shape.values().copied().collect();
+    assert_eq!(cells_by_shape.len(), BLOB_TILE_COUNT);
+    assert_eq!(cells.len(), BLOB_TILE_COUNT);
+}
+
+#[test]
+fn blob_indices_follow_the_mask_order() {
+    assert_eq!(BlobMask(0).blob_index(), 0);
+    assert_eq!(BlobMask(BlobMask::NORTH_EAST | BlobMask::SOUTH_WEST).blob_index(), 0);
+    assert_eq!(BlobMask(BlobMask::NORTH).blob_index(), 1);
+    assert_eq!(BlobMask(BlobMask::NORTH | BlobMask::SOUTH).blob_index(), 6);
+    assert_eq!(BlobMask(BlobMask::EAST | BlobMask::WEST).blob_index(), 15);
+    assert_eq!(BlobMask(255).blob_index(), BLOB_TILE_COUNT - 1);
+}
+
+#[test]
+fn isolated_tile_and_corridor_get_their_own_blob_cells() {
+    let grid = grid_from_rows(&[
+        ".....",
+        ".#...",
+        ".....",
+        "#####",
+        "....."
+    ]);
+
+    assert_eq!(BlobMask::sample(&grid, TilePos::new(1, 1), |&it| it), BlobMask(0));
+    assert_eq!(
+        BlobMask::sample(&grid, TilePos::new(2, 3), |&it| it),
+        BlobMask(BlobMask::EAST | BlobMask::WEST)
+    );
+    // Tiles outside the grid repeat the edge, so the corridor continues past the border.
+    assert_eq!(
+        BlobMask::sample(&grid, TilePos::new(0, 3), |&it| it),
+        BlobMask(BlobMask::EAST | BlobMask::WEST)
+    );
+
+    let mut sprites = Vec::new();
+    BLOB.for_each_sprite(&grid, |&it| it, |dest, coords| sprites.push((dest, coords)));
+    assert_eq!(sprites.len(), 6);
+    assert_eq!(sprites[0], ([32, 32], [1000, 2000]));
+    for (i, &(dest, coords)) in sprites[1..].iter().enumerate() {
+        assert_eq!(dest, [i as isize * 32, 96]);
+        assert_eq!(coords, [1000 + 7 * 32, 2000 + 32]);
+    }
+}
+
+#[test]
+fn wang_sprites_sit_on_tile_corners() {
+    let grid = grid_from_rows(&[
+        "...",
+        ".#.",
+        "..."
+    ]);
+
+    let mut sprites = Vec::new();
+    WANG.for_each_sprite(&grid, |&it| it, |dest, coords| sprites.push((dest, coords)));
+    assert_eq!(
+        sprites,
+        vec![
+            ([16, 16], [1000 + 64, 2000]),
+            ([48, 16], [1000 + 96, 2000]),
+            ([16, 48], [1000 + 64, 2000 + 32]),
+            ([48, 48], [1000 + 96, 2000 + 32])
+        ]
+    );
+}
+
+#[test]
+fn empty_grid_draws_nothing() {
+    let grid: Grid<bool> = Grid::new(0, 0, false);
+    let mut count = 0;
+    WANG.for_each_sprite(&grid, |&it| it, |_, _| count += 1);
+    BLOB.for_each_sprite(&grid, |&it| it, |_, _| count += 1);
+    assert_eq!(count, 0);
+}
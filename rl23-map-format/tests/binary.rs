@@ -0,0 +1,110 @@
+mod common;
+
+use rl23_map_format::{
+    MapFormat,
+    MapFormatError,
+    MapInfo,
+    TerrainKind,
+    CURRENT_FORMAT_VERSION,
+    MAX_BINARY_MAP_TILES
+};
+use common::{fixture_path, temp_path};
+
+#[test]
+fn ron_to_binary_to_ron_round_trip_is_lossless() {
+    let original = MapInfo::read_from_path(&fixture_path("v1.ron")).unwrap();
+
+    let binary_path = temp_path("map.rlmap");
+    assert_eq!(MapFormat::from_path(&binary_path), MapFormat::Binary);
+    original.save_to_path(&binary_path).unwrap();
+    assert!(std::fs::read(&binary_path).unwrap().starts_with(b"RL23MAP\0"));
+
+    let ron_path = temp_path("map.ron");
+    MapInfo::read_from_path(&binary_path).unwrap().save_to_path(&ron_path).unwrap();
+    let converted = MapInfo::read_from_path(&ron_path).unwrap();
+
+    assert_eq!(
+        original.to_canonical_string().unwrap(),
+        converted.to_canonical_string().unwrap()
+    );
+}
+
+#[test]
+fn large_uniform_layers_are_run_length_encoded() {
+    let mut map = MapInfo::create_new(256, 256, 0);
+    for (_, terrain) in map.terrain_layer.iter_mut() {
+        *terrain = TerrainKind::Grass;
+    }
+    assert!(map.to_binary().unwrap().len() < 256);
+}
+
+fn grass_map_bytes() -> Vec<u8> {
+    let mut map = MapInfo::create_new(4, 4, 0);
+    for (_, terrain) in map.terrain_layer.iter_mut() {
+        *terrain = TerrainKind::Grass;
+    }
+    map.to_binary().unwrap()
+}
+
+fn read_bytes(name: &str, bytes: &[u8]) -> Result<MapInfo, MapFormatError> {
+    let path = temp_path(name);
+    std::fs::write(&path, bytes).unwrap();
+    MapInfo::read_from_path(&path)
+}
+
+#[test]
+fn other_versions_are_rejected_with_matching_errors() {
+    let mut bytes = grass_map_bytes();
+    bytes[8..12].copy_from_slice(&(CURRENT_FORMAT_VERSION - 1).to_le_bytes());
+    assert!(matches!(
+        read_bytes("old.rlmap", &bytes),
+        Err(MapFormatError::OutdatedBinary { found, current })
+            if found == CURRENT_FORMAT_VERSION - 1 && current == CURRENT_FORMAT_VERSION
+    ));
+
+    bytes[8..12].copy_from_slice(&(CURRENT_FORMAT_VERSION + 1).to_le_bytes());
+    assert!(matches!(
+        read_bytes("new.rlmap", &bytes),
+        Err(MapFormatError::UnsupportedVersion { found, .. }) if found == CURRENT_FORMAT_VERSION + 1
+    ));
+}
+
+#[test]
+fn runs_must_cover_the_map_exactly() {
+    // width, height, a single terrain run, and the length of that run.
+    let run: Vec<u8> = [4u32.to_le_bytes(), 4u32.to_le_bytes()]
+        .concat()
+        .into_iter()
+        .chain(1u64.to_le_bytes())
+        .chain(16u32.to_le_bytes())
+        .collect();
+    let bytes = grass_map_bytes();
+    let at = bytes.windows(run.len()).position(|window| window == run).unwrap() + run.len() - 4;
+
+    for length in [15u32, u32::MAX] {
+        let mut corrupt = bytes.clone();
+        corrupt[at..at + 4].copy_from_slice(&length.to_le_bytes());
+        assert!(matches!(
+            read_bytes("corrupt.rlmap", &corrupt),
+            Err(MapFormatError::LayerSizeMismatch { layer: "terrain_layer", expected: 16, actual })
+                if actual == length as usize
+        ));
+    }
+}
+
+#[test]
+fn huge_headers_are_rejected_before_allocating() {
+    // width and height, followed by the count of terrain runs.
+    let size: Vec<u8> = [4u32.to_le_bytes(), 4u32.to_le_bytes()].concat().into_iter().chain(1u64.to_le_bytes()).collect();
+    let bytes = grass_map_bytes();
+    let at = bytes.windows(size.len()).position(|window| window == size).unwrap();
+
+    let side = (MAX_BINARY_MAP_TILES as f64).sqrt() as u32 + 1;
+    let mut corrupt = bytes.clone();
+    corrupt[at..at + 4].copy_from_slice(&side.to_le_bytes());
+    corrupt[at + 4..at + 8].copy_from_slice(&side.to_le_bytes());
+    assert!(matches!(
+        read_bytes("huge.rlmap", &corrupt),
+        Err(MapFormatError::MapTooLarge { width, height }) if width == side as usize && height == side as usize
+    ));
+}
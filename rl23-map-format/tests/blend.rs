@@ -0,0 +1,78 @@
+mod common;
+
+use rl23_map_format::{Autotile, MapInfo, TerrainBlend, TerrainKind, Tileset, TilePos, WangEncoding};
+use common::fixture_path;
+
+#[test]
+fn default_tileset_keeps_the_historical_draw_order() {
+    let tileset = Tileset::default();
+    assert_eq!(tileset.terrain_blend, TerrainBlend::default());
+    assert_eq!(tileset.terrain_blend.draw_order(), TerrainBlend::default().priority);
+}
+
+#[test]
+fn kinds_missing_from_the_priority_are_drawn_first() {
+    let blend = TerrainBlend {
+        base: TerrainKind::Mud { offset: 0 },
+        priority: vec![
+            TerrainKind::Tile,
+            TerrainKind::Mud { offset: 3 },
+            TerrainKind::Grass,
+            TerrainKind::Tile
+        ]
+    };
+    let order = blend.draw_order();
+    assert_eq!(order.len(), TerrainKind::WANG_KINDS.len());
+    assert_eq!(&order[order.len() - 2..], &[TerrainKind::Tile, TerrainKind::Grass]);
+}
+
+#[test]
+fn map_override_changes_which_terrain_is_drawn_on_top() {
+    let tileset = Tileset::default();
+    let mut map = MapInfo::create_new(2, 1, 0);
+    map.terrain_layer.set(TilePos::new(0, 0), TerrainKind::Grass);
+    map.terrain_layer.set(TilePos::new(1, 0), TerrainKind::Tile);
+
+    let grass = tileset.terrain(TerrainKind::Grass).unwrap();
+    let tile = tileset.terrain(TerrainKind::Tile).unwrap();
+    let last_sprite = |map: &MapInfo| {
+        let mut sprites = Vec::new();
+        tileset.for_each_terrain_sprite(map, |_, coords| sprites.push(coords));
+        *sprites.last().unwrap()
+    };
+    let topmost_kind = |coords: [usize; 2]| {
+        let in_set = |autotile: Autotile| {
+            (0..16).any(|bits| autotile.wang_coords(WangEncoding::from_bits(bits)) == Some(coords))
+        };
+        if in_set(grass) {
+            TerrainKind::Grass
+        } else if in_set(tile) {
+            TerrainKind::Tile
+        } else {
+            panic!("{:?} is neither grass nor tile", coords)
+        }
+    };
+
+    assert_eq!(topmost_kind(last_sprite(&map)), TerrainKind::Grass);
+
+    map.terrain_blend = Some(TerrainBlend {
+        base: TerrainKind::Mud { offset: 0 },
+        priority: vec![TerrainKind::Grass, TerrainKind::Tile]
+    });
+    assert_eq!(topmost_kind(last_sprite(&map)), TerrainKind::Tile);
+}
+
+#[test]
+fn non_mud_base_fills_every_tile() {
+    let tileset = Tileset::default();
+    let map = MapInfo::read_from_path(&fixture_path("v2.ron")).unwrap();
+    let blend = map.terrain_blend(&tileset);
+    assert_eq!(blend.base, TerrainKind::Sand);
+
+    let full_sand = tileset.terrain(TerrainKind::Sand).unwrap().full_coords().unwrap();
+    let mut sprites = Vec::new();
+    tileset.for_each_terrain_sprite(&map, |dest, coords| sprites.push((dest, coords)));
+    let base: Vec<_> = sprites[..map.bounds().len()].to_vec();
+    assert!(base.iter().all(|&(_, coords)| coords == full_sand));
+    assert_eq!(base[4].0, [32, 32]);
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use rl23_map_format::{MapInfo, TerrainKind, TilePos};
+use common::{fixture_path, temp_path};
+
+#[test]
+fn saving_an_unchanged_map_is_byte_identical() {
+    let path = temp_path("resaved.ron");
+    let map = MapInfo::read_from_path(&fixture_path("v1.ron")).unwrap();
+    map.save_to_path(&path).unwrap();
+    let first = std::fs::read(&path).unwrap();
+
+    MapInfo::read_from_path(&path).unwrap().save_to_path(&path).unwrap();
+    let second = std::fs::read(&path).unwrap();
+    assert_eq!(first, second);
+}
+
+#[test]
+fn single_tile_edit_changes_a_single_line() {
+    let mut map = MapInfo::read_from_path(&fixture_path("v1.ron")).unwrap();
+    let before = map.to_canonical_string().unwrap();
+    map.terrain_layer.set(TilePos::new(1, 1), TerrainKind::Lava);
+    let after = map.to_canonical_string().unwrap();
+
+    let before_lines: Vec<_> = before.lines().collect();
+    let after_lines: Vec<_> = after.lines().collect();
+    assert_eq!(before_lines.len(), after_lines.len());
+    let changed = before_lines.iter().zip(after_lines.iter()).filter(|(a, b)| a != b).count();
+    assert_eq!(changed, 1);
+}
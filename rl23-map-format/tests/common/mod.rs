@@ -0,0 +1,81 @@
+//! Helpers shared by the integration tests. Every test binary uses only some of them.
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+use rl23_map_format::{
+    ClosedDoor,
+    EntityComponentData,
+    Light,
+    MapEntity,
+    MapInfo,
+    PlacedEntity,
+    RegionShape,
+    TerrainKind,
+    TilePos,
+    Tree,
+    Unit,
+    WallKind
+};
+
+pub fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+/// Path in a temporary directory of the running test binary.
+pub fn temp_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rl23-{}-{}", env!("CARGO_CRATE_NAME"), std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+pub fn rect(x: usize, y: usize, width: usize, height: usize) -> RegionShape {
+    RegionShape::Rect { min: TilePos::new(x, y), width, height }
+}
+
+/// Builds a map from rows of characters:
+/// `.` mud, `~` water, `,` cave water, `^` lava, `#` brick wall, `T` birch, `D` closed door, `W` wolf,
+/// `*` candle (a logic entity with a light component), `@` mud marking a spot found with `find_in_rows`.
+pub fn map_from_rows(rows: &[&str]) -> MapInfo {
+    let mut map = MapInfo::create_new(rows[0].len(), rows.len(), 0);
+    for (y, row) in rows.iter().enumerate() {
+        for (x, c) in row.chars().enumerate() {
+            let pos = TilePos::new(x, y);
+            let terrain = match c {
+                '~' => TerrainKind::Water,
+                ',' => TerrainKind::CaveWater,
+                '^' => TerrainKind::Lava,
+                _ => TerrainKind::Mud { offset: 0 }
+            };
+            map.terrain_layer.set(pos, terrain);
+            let entity = match c {
+                '#' => {
+                    map.wall_layer.set(pos, Some(WallKind::Bricks));
+                    None
+                }
+                'T' => Some((MapEntity::Tree(Tree::Birch), Vec::new())),
+                'D' => Some((MapEntity::ClosedDoor(ClosedDoor::Brown), Vec::new())),
+                'W' => Some((MapEntity::Unit(Unit::Wolf), Vec::new())),
+                '*' => Some((MapEntity::Logic, vec![EntityComponentData::Light(Light {
+                    radius: 3,
+                    color_index: 8,
+                    intensity: 12
+                })])),
+                _ => None
+            };
+            if let Some((entity, components)) = entity {
+                let mut placed = PlacedEntity::new(map.id_generator.generate(), pos, entity);
+                placed.components = components;
+                map.entities.insert(placed);
+            }
+        }
+    }
+    map
+}
+
+/// Position of the first `c` in `rows`.
+pub fn find_in_rows(rows: &[&str], c: char) -> TilePos {
+    rows.iter()
+        .enumerate()
+        .find_map(|(y, row)| row.chars().position(|it| it == c).map(|x| TilePos::new(x, y)))
+        .unwrap()
+}
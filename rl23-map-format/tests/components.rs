@@ -0,0 +1,100 @@
+#![cfg(feature = "editor-ui")]
+
+use rl23_map_format::egui::{self, epaint, Context, Event, Pos2, RawInput};
+use rl23_map_format::{
+    complete_entity_pick,
+    entity_pick_pending,
+    pick_entity_field,
+    EntityComponent,
+    EntityComponentDataImpl,
+    IdGenerator,
+    Light,
+    MapEntity,
+    SpawnRandomUnit,
+    Tree,
+    Unit
+};
+
+#[derive(Clone, Default, EntityComponent)]
+#[component(label = "Shrine Blessing", applies_to(Decor, Tree))]
+struct ShrineBlessing {
+    #[component(range = 1..=10)]
+    _strength: u8,
+    #[component(skip)]
+    _cooldown: u32
+}
+
+#[derive(Clone, Default, EntityComponent)]
+struct NightOnly {
+    _enabled: bool
+}
+
+#[test]
+fn labels_come_from_attributes_or_struct_names() {
+    assert_eq!(SpawnRandomUnit::LABEL, "Spawn Random Unit");
+    assert_eq!(Light::LABEL, "Light");
+    assert_eq!(ShrineBlessing::LABEL, "Shrine Blessing");
+    assert_eq!(NightOnly::LABEL, "Night Only");
+}
+
+#[test]
+fn applicability_follows_attributes() {
+    assert!(SpawnRandomUnit::is_applicable_for_enitity_type(MapEntity::Logic));
+    assert!(!SpawnRandomUnit::is_applicable_for_enitity_type(MapEntity::Loot));
+
+    assert!(Light::is_applicable_for_enitity_type(MapEntity::Unit(Unit::Leshy)));
+    assert!(!Light::is_applicable_for_enitity_type(MapEntity::Loot));
+
+    assert!(ShrineBlessing::is_applicable_for_enitity_type(MapEntity::Tree(Tree::Oak)));
+    assert!(!ShrineBlessing::is_applicable_for_enitity_type(MapEntity::Logic));
+
+    assert!(NightOnly::is_applicable_for_enitity_type(MapEntity::Door));
+}
+
+#[test]
+fn new_components_start_from_their_defaults() {
+    let mut id_generator = IdGenerator::default();
+    assert_eq!(Light::make_default(&mut id_generator), Light::default());
+    assert_eq!(SpawnRandomUnit::make_default(&mut id_generator), SpawnRandomUnit::default());
+}
+
+fn pick_frame(ctx: &Context, events: Vec<Event>, value: &mut u64) -> Option<Pos2> {
+    let output = ctx.run(RawInput { events, ..Default::default() }, |ctx| {
+        egui::CentralPanel::default().show(ctx, |ui| pick_entity_field(value, "target", ui));
+    });
+    output.shapes.iter().find_map(|clipped| match &clipped.1 {
+        epaint::Shape::Text(text) if text.galley.text() == "pick" => Some(text.pos),
+        _ => None
+    })
+}
+
+fn click(pos: Pos2, pressed: bool) -> Vec<Event> {
+    vec![
+        Event::PointerMoved(pos),
+        Event::PointerButton {
+            pos,
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: Default::default()
+        }
+    ]
+}
+
+#[test]
+fn picked_entity_is_written_into_the_waiting_field() {
+    let ctx = Context::default();
+    let mut value = 0;
+    let pick_button = pick_frame(&ctx, Vec::new(), &mut value).expect("pick button is drawn") + egui::vec2(2.0, 2.0);
+    pick_frame(&ctx, click(pick_button, true), &mut value);
+    pick_frame(&ctx, click(pick_button, false), &mut value);
+    assert!(entity_pick_pending(&ctx));
+
+    complete_entity_pick(&ctx, 42);
+    assert!(!entity_pick_pending(&ctx));
+    pick_frame(&ctx, Vec::new(), &mut value);
+    assert_eq!(value, 42);
+
+    value = 7;
+    pick_frame(&ctx, Vec::new(), &mut value);
+    assert_eq!(value, 7);
+}
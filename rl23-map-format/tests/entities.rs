@@ -0,0 +1,75 @@
+use rl23_map_format::{
+    map_rng,
+    EntityComponentData,
+    EntityTable,
+    MapEntity,
+    MapInfo,
+    PlacedEntity,
+    ResizeAnchor,
+    SpawnRandomUnit,
+    TilePos
+};
+
+fn spawner(id: u64, position: TilePos) -> PlacedEntity {
+    let mut entity = PlacedEntity::new(id, position, MapEntity::Logic);
+    entity.components.push(EntityComponentData::SpawnRandomUnit(SpawnRandomUnit {
+        min_level: 2,
+        max_level: 4
+    }));
+    entity
+}
+
+#[test]
+fn several_entities_share_a_tile() {
+    let mut table = EntityTable::new(4, 4);
+    let pos = TilePos::new(1, 2);
+    assert!(table.insert(spawner(7, pos)));
+    assert!(table.insert(PlacedEntity::new(3, pos, MapEntity::Loot)));
+    assert!(!table.insert(PlacedEntity::new(4, TilePos::new(4, 0), MapEntity::Loot)));
+
+    assert_eq!(table.ids_at(pos), &[3, 7]);
+    let kinds: Vec<_> = table.at(pos).map(|entity| entity.entity).collect();
+    assert_eq!(kinds, vec![MapEntity::Loot, MapEntity::Logic]);
+
+    table.remove(3);
+    assert_eq!(table.ids_at(pos), &[7]);
+    table.remove(7);
+    assert!(!table.is_occupied(pos));
+    assert!(table.is_empty());
+}
+
+#[test]
+fn moving_an_entity_keeps_its_id_and_components() {
+    let mut table = EntityTable::new(4, 4);
+    let from = TilePos::new(0, 0);
+    let to = TilePos::new(3, 1);
+    table.insert(spawner(5, from));
+
+    assert!(table.move_to(5, to));
+    assert!(!table.move_to(5, TilePos::new(0, 9)));
+    assert!(!table.move_to(6, to));
+
+    assert!(table.ids_at(from).is_empty());
+    assert_eq!(table.ids_at(to), &[5]);
+    let moved = table.get(5).unwrap();
+    assert_eq!(moved.position(), to);
+    assert_eq!(moved, &spawner(5, to));
+}
+
+#[test]
+fn resize_shifts_entities_and_reports_the_dropped_ones() {
+    let mut map = MapInfo::create_new(4, 4, 0);
+    let kept = map.id_generator.generate();
+    let dropped = map.id_generator.generate();
+    map.entities.insert(spawner(kept, TilePos::new(3, 3)));
+    map.entities.insert(spawner(dropped, TilePos::new(0, 0)));
+
+    let report = map.resize(2, 2, ResizeAnchor::BottomRight, &mut map_rng(0)).unwrap();
+
+    assert_eq!(map.entities.get(kept).unwrap().position(), TilePos::new(1, 1));
+    assert_eq!(map.entities.ids_at(TilePos::new(1, 1)), &[kept]);
+    assert_eq!(report.dropped_entities.len(), 1);
+    assert_eq!(report.dropped_entities[0].id(), dropped);
+    assert_eq!(report.dropped_entities[0].position(), TilePos::new(0, 0));
+    assert_eq!(report.dropped_entities[0].components.len(), 1);
+}
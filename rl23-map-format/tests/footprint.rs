@@ -0,0 +1,105 @@
+use rl23_map_format::{
+    ClosedDoor,
+    Decor,
+    MapEntity,
+    MapInfo,
+    PlacedEntity,
+    PlacementConflict,
+    TilePos,
+    Tree,
+    Unit,
+    ValidationIssueKind,
+    MAX_FOOTPRINT_REACH
+};
+
+fn all_entities() -> Vec<MapEntity> {
+    let mut entities = vec![MapEntity::Door, MapEntity::Loot, MapEntity::Logic];
+    entities.extend(ClosedDoor::ALL.map(MapEntity::ClosedDoor));
+    entities.extend(Decor::ALL.map(MapEntity::Decor));
+    entities.extend(Unit::ALL.map(MapEntity::Unit));
+    entities.extend(Tree::ALL.map(MapEntity::Tree));
+    entities
+}
+
+fn place(map: &mut MapInfo, entity: MapEntity, x: usize, y: usize) -> u64 {
+    let id = map.id_generator.generate();
+    assert!(map.entities.insert(PlacedEntity::new(id, TilePos::new(x, y), entity)));
+    id
+}
+
+#[test]
+fn footprints_cover_their_origin_and_stay_within_reach() {
+    let reach = MAX_FOOTPRINT_REACH as isize;
+    for entity in all_entities() {
+        let footprint = entity.footprint();
+        assert!(footprint.iter().any(|cell| (cell.dx, cell.dy) == (0, 0)), "{:?}", entity);
+        for cell in footprint {
+            assert!(cell.dx.abs() <= reach && cell.dy.abs() <= reach, "{:?}", entity);
+            assert!(cell.solid || !cell.blocks_movement, "{:?}", entity);
+        }
+    }
+}
+
+#[test]
+fn second_half_of_a_bed_is_occupied() {
+    let mut map = MapInfo::create_new(4, 3, 0);
+    let bed = place(&mut map, MapEntity::Decor(Decor::Bed1GreenLeft), 1, 1);
+
+    let occupants = map.occupants(TilePos::new(2, 1));
+    assert_eq!(occupants.len(), 1);
+    assert_eq!(occupants[0].0.id(), bed);
+    assert!(map.is_blocked_by_entity(TilePos::new(2, 1)));
+    assert!(map.occupants(TilePos::new(3, 1)).is_empty());
+
+    assert_eq!(
+        map.placement_conflicts(MapEntity::Unit(Unit::Wolf), TilePos::new(2, 1), None),
+        vec![PlacementConflict::Overlaps { id: bed, position: TilePos::new(2, 1) }]
+    );
+    assert!(map.placement_conflicts(MapEntity::Logic, TilePos::new(2, 1), None).is_empty());
+    assert!(map.placement_conflicts(MapEntity::Unit(Unit::Wolf), TilePos::new(2, 1), Some(bed)).is_empty());
+    assert_eq!(
+        map.placement_conflicts(MapEntity::Decor(Decor::TableBlue), TilePos::new(3, 0), None),
+        vec![PlacementConflict::OutOfBounds]
+    );
+}
+
+#[test]
+fn overlaps_are_reported_once_by_validation() {
+    let mut map = MapInfo::create_new(5, 3, 0);
+    let oak = place(&mut map, MapEntity::Tree(Tree::Oak), 2, 1);
+    let wolf = place(&mut map, MapEntity::Unit(Unit::Wolf), 3, 1);
+    place(&mut map, MapEntity::Logic, 2, 1);
+
+    // The branches of the oak can be walked under, but nothing can be placed there.
+    assert!(!map.is_blocked_by_entity(TilePos::new(1, 1)));
+    assert!(!map.placement_conflicts(MapEntity::Unit(Unit::Wolf), TilePos::new(1, 1), None).is_empty());
+
+    let overlaps: Vec<_> = map
+        .validate()
+        .into_iter()
+        .filter_map(|issue| match issue.kind {
+            ValidationIssueKind::OverlappingEntities { id, other } => Some((id, other)),
+            _ => None
+        })
+        .collect();
+    assert_eq!(overlaps, vec![(oak, wolf)]);
+
+    // Tables sharing both of their tiles are still a single overlap.
+    let mut map = MapInfo::create_new(4, 3, 0);
+    let blue = place(&mut map, MapEntity::Decor(Decor::TableBlue), 1, 1);
+    let green = place(&mut map, MapEntity::Decor(Decor::TableGreen), 1, 1);
+    assert_eq!(
+        map.placement_conflicts(MapEntity::Decor(Decor::TableGreen), TilePos::new(1, 1), Some(green)),
+        vec![PlacementConflict::Overlaps { id: blue, position: TilePos::new(1, 1) }]
+    );
+    let overlaps: Vec<_> = map
+        .validate()
+        .into_iter()
+        .filter(|issue| matches!(issue.kind, ValidationIssueKind::OverlappingEntities { .. }))
+        .map(|issue| (issue.kind, issue.position))
+        .collect();
+    assert_eq!(overlaps, vec![(
+        ValidationIssueKind::OverlappingEntities { id: blue, other: green },
+        Some(TilePos::new(1, 1))
+    )]);
+}
@@ -0,0 +1,110 @@
+use rl23_map_format::{Grid, GridBounds, SparseGrid, TilePos};
+
+fn numbered(width: usize, height: usize) -> Grid<usize> {
+    Grid::from_fn(width, height, |pos| pos.y * 10 + pos.x)
+}
+
+#[test]
+fn positions_go_row_by_row() {
+    let bounds = GridBounds::new(3, 2);
+    let positions: Vec<_> = bounds.positions().collect();
+    assert_eq!(positions, vec![
+        TilePos::new(0, 0), TilePos::new(1, 0), TilePos::new(2, 0),
+        TilePos::new(0, 1), TilePos::new(1, 1), TilePos::new(2, 1)
+    ]);
+    let mut sorted = positions.clone();
+    sorted.sort();
+    assert_eq!(sorted, positions);
+
+    assert_eq!(bounds.index_of(TilePos::new(2, 1)), Some(5));
+    assert_eq!(bounds.index_of(TilePos::new(3, 0)), None);
+    assert_eq!(bounds.pos_of(4), Some(TilePos::new(1, 1)));
+    assert_eq!(bounds.pos_of(6), None);
+    assert_eq!(TilePos::new(0, 1).offset(-1, 0), None);
+    assert_eq!(TilePos::new(0, 1).offset(2, -1), Some(TilePos::new(2, 0)));
+}
+
+#[test]
+fn neighbours_stay_inside_the_bounds() {
+    let bounds = GridBounds::new(3, 3);
+    let neighbours4 = |x, y| bounds.neighbours4(TilePos::new(x, y)).collect::<Vec<_>>();
+    let neighbours8 = |x, y| bounds.neighbours8(TilePos::new(x, y)).collect::<Vec<_>>();
+
+    assert_eq!(neighbours4(0, 0), vec![TilePos::new(1, 0), TilePos::new(0, 1)]);
+    assert_eq!(neighbours4(2, 1), vec![TilePos::new(2, 0), TilePos::new(2, 2), TilePos::new(1, 1)]);
+    assert_eq!(neighbours4(1, 1).len(), 4);
+
+    assert_eq!(neighbours8(2, 2), vec![TilePos::new(2, 1), TilePos::new(1, 2), TilePos::new(1, 1)]);
+    assert_eq!(neighbours8(1, 0).len(), 5);
+    assert_eq!(neighbours8(1, 1).len(), 8);
+
+    let single = GridBounds::new(1, 1);
+    assert_eq!(single.neighbours8(TilePos::new(0, 0)).count(), 0);
+}
+
+#[test]
+fn rect_is_clipped_to_the_bounds() {
+    let grid = numbered(4, 3);
+    let values = |min, width, height| {
+        grid.rect(min, width, height).map(|pos| *grid.get(pos).unwrap()).collect::<Vec<_>>()
+    };
+    assert_eq!(values(TilePos::new(1, 1), 2, 2), vec![11, 12, 21, 22]);
+    assert_eq!(values(TilePos::new(2, 2), 5, 5), vec![22, 23]);
+    assert_eq!(values(TilePos::new(1, 0), usize::MAX, 1), vec![1, 2, 3]);
+    assert!(values(TilePos::new(4, 0), 2, 2).is_empty());
+    assert!(values(TilePos::new(0, 0), 0, 3).is_empty());
+}
+
+#[test]
+fn clamped_reads_repeat_the_edges() {
+    let grid = numbered(3, 2);
+    assert_eq!(*grid.get_clamped(-5, -1), 0);
+    assert_eq!(*grid.get_clamped(1, 7), 11);
+    assert_eq!(*grid.get_clamped(9, 0), 2);
+    assert_eq!(*grid.get_clamped(2, 1), 12);
+}
+
+#[test]
+#[should_panic]
+fn clamped_reads_of_an_empty_grid_panic() {
+    Grid::new(0, 2, 0u8).get_clamped(0, 0);
+}
+
+#[test]
+fn dense_grid_checks_sizes_and_bounds() {
+    assert_eq!(Grid::from_vec(2, 2, vec![1, 2, 3]), Err(vec![1, 2, 3]));
+    assert_eq!(Grid::from_vec(usize::MAX, 2, vec![1, 2]), Err(vec![1, 2]));
+    let mut grid = Grid::from_vec(2, 2, vec![1, 2, 3, 4]).unwrap();
+    assert!(grid.set(TilePos::new(1, 1), 9));
+    assert!(!grid.set(TilePos::new(2, 0), 9));
+    assert_eq!(grid.get(TilePos::new(0, 2)), None);
+    assert_eq!(grid.into_vec(), vec![1, 2, 3, 9]);
+
+    let huge = GridBounds::new(usize::MAX, 3);
+    assert_eq!(huge.checked_len(), None);
+    assert_eq!(huge.pos_of(usize::MAX - 1), Some(TilePos::new(usize::MAX - 1, 0)));
+
+    let empty = Grid::new(0, 0, 0u8);
+    assert!(empty.bounds().is_empty());
+    assert_eq!(empty.iter().count(), 0);
+}
+
+#[test]
+fn sparse_grid_keeps_row_major_order() {
+    let mut grid = SparseGrid::new(4, 3);
+    assert!(grid.insert(TilePos::new(3, 0), 'c'));
+    assert!(grid.insert(TilePos::new(0, 2), 'd'));
+    assert!(grid.insert(TilePos::new(1, 0), 'b'));
+    assert!(!grid.insert(TilePos::new(4, 0), 'x'));
+    assert!(!grid.insert(TilePos::new(0, 3), 'x'));
+    assert_eq!(grid.len(), 3);
+
+    let values: String = grid.values().collect();
+    assert_eq!(values, "bcd");
+    let in_rect: Vec<_> = grid.rect(TilePos::new(1, 0), 9, 9).map(|(pos, &value)| (pos, value)).collect();
+    assert_eq!(in_rect, vec![(TilePos::new(1, 0), 'b'), (TilePos::new(3, 0), 'c')]);
+
+    grid.retain(|pos, _| pos.y == 0);
+    assert_eq!(grid.remove(TilePos::new(1, 0)), Some('b'));
+    assert_eq!(grid.into_iter().collect::<Vec<_>>(), vec![(TilePos::new(3, 0), 'c')]);
+}
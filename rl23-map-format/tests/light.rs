@@ -0,0 +1,79 @@
+mod common;
+
+use rl23_map_format::{
+    LightLevel,
+    MapInfo,
+    ShadeRamps,
+    TerrainKind,
+    TilePos,
+    LAVA_LIGHT_COLOR,
+    MAX_LIGHT_LEVEL
+};
+use common::map_from_rows;
+
+fn lit_map(rows: &[&str], ambient_light: LightLevel) -> MapInfo {
+    let mut map = map_from_rows(rows);
+    map.ambient_light = ambient_light;
+    map
+}
+
+/// Light levels as hexadecimal digits.
+fn level_rows(map: &MapInfo) -> Vec<String> {
+    let light_map = map.light_map();
+    (0..map.height())
+        .map(|y| {
+            (0..map.width())
+                .map(|x| {
+                    let level = light_map.level(TilePos::new(x, y)).unwrap();
+                    char::from_digit(level as u32, 16).unwrap()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn light_fades_with_distance_and_stops_at_walls() {
+    let map = lit_map(&[
+        "........",
+        "...*.#..",
+        ".....#..",
+        "........"
+    ], 1);
+    assert_eq!(level_rows(&map), vec![
+        "47aaa741",
+        "47ada711",
+        "47aaa711",
+        "17777711"
+    ]);
+}
+
+#[test]
+fn lava_glows_in_its_own_colour() {
+    let map = lit_map(&[
+        ".....",
+        "..^..",
+        "....."
+    ], 0);
+    let light_map = map.light_map();
+    assert_eq!(map.light_sources().len(), 1);
+    assert_eq!(light_map.color(TilePos::new(2, 1)), Some(LAVA_LIGHT_COLOR));
+    assert_eq!(light_map.level(TilePos::new(2, 1)), TerrainKind::Lava.light().map(|light| light.intensity));
+    assert!(light_map.level(TilePos::new(0, 0)).unwrap() > 0);
+}
+
+#[test]
+fn overlapping_lights_add_up_to_full_brightness() {
+    let map = lit_map(&["*.*"], MAX_LIGHT_LEVEL - 2);
+    assert_eq!(map.light_map().level(TilePos::new(1, 0)), Some(MAX_LIGHT_LEVEL));
+}
+
+#[test]
+fn shade_ramps_darken_towards_the_darkest_colour() {
+    let palette = [[0, 0, 0], [128, 128, 128], [255, 255, 255]];
+    let ramps = ShadeRamps::new(&palette);
+    assert_eq!(ramps.shade(2, MAX_LIGHT_LEVEL), 2);
+    assert_eq!(ramps.shade(2, MAX_LIGHT_LEVEL / 2), 1);
+    assert_eq!(ramps.shade(2, 0), 0);
+    assert_eq!(ramps.shade(1, 0), 0);
+}
@@ -0,0 +1,175 @@
+mod common;
+
+use std::path::PathBuf;
+use rl23_map_format::{
+    map_rng,
+    LootDrop,
+    LootEntry,
+    LootSource,
+    LootTable,
+    LootTables,
+    MapFormatError
+};
+
+fn entry(source: LootSource, weight: u32, min_quantity: u32, max_quantity: u32) -> LootEntry {
+    LootEntry { source, weight, min_quantity, max_quantity }
+}
+
+fn item(name: &str) -> LootSource {
+    LootSource::Item(name.to_string())
+}
+
+fn shared_tables() -> LootTables {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../assets/loot_tables.ron");
+    LootTables::read_from_path(&path).unwrap()
+}
+
+#[test]
+fn rolls_are_reproducible() {
+    let tables = shared_tables();
+    let chest = tables.get("forest_chest").unwrap();
+    let rolls = |seed| (0..20).map(|_| chest.roll(&tables, &mut map_rng(seed)).unwrap()).collect::<Vec<_>>();
+    assert_eq!(rolls(5), rolls(5));
+
+    let mut rng = map_rng(5);
+    let many: Vec<_> = (0..20).map(|_| chest.roll(&tables, &mut rng).unwrap()).collect();
+    assert!(many.windows(2).any(|pair| pair[0] != pair[1]));
+}
+
+#[test]
+fn guaranteed_drops_always_drop() {
+    let table = LootTable {
+        rolls: 0,
+        guaranteed: vec![entry(item("key"), 1, 1, 1), entry(item("coin"), 0, 2, 2), entry(item("key"), 1, 1, 1)],
+        entries: vec![entry(item("never"), 1, 1, 1)]
+    };
+    let drops = table.roll(&LootTables::default(), &mut map_rng(0)).unwrap();
+    assert_eq!(drops, vec![
+        LootDrop { item: "key".to_string(), quantity: 2 },
+        LootDrop { item: "coin".to_string(), quantity: 2 }
+    ]);
+}
+
+#[test]
+fn nested_tables_are_rolled_once_per_quantity() {
+    let tables = shared_tables();
+    let table = LootTable {
+        rolls: 0,
+        guaranteed: vec![entry(LootSource::Table("wolf".to_string()), 1, 3, 3)],
+        entries: Vec::new()
+    };
+    let drops = table.roll(&tables, &mut map_rng(1)).unwrap();
+    assert_eq!(drops[0], LootDrop { item: "wolf_pelt".to_string(), quantity: 3 });
+}
+
+#[test]
+fn missing_and_cyclic_tables_are_errors() {
+    let missing = LootTable {
+        rolls: 1,
+        guaranteed: Vec::new(),
+        entries: vec![entry(LootSource::Table("bandit".to_string()), 1, 1, 1)]
+    };
+    assert!(matches!(
+        missing.roll(&LootTables::default(), &mut map_rng(0)),
+        Err(MapFormatError::UnknownLootTable { name }) if name == "bandit"
+    ));
+
+    let mut tables = LootTables::default();
+    tables.tables.insert("ouroboros".to_string(), LootTable {
+        rolls: 0,
+        guaranteed: vec![entry(LootSource::Table("ouroboros".to_string()), 1, 1, 1)],
+        entries: Vec::new()
+    });
+    assert!(matches!(
+        tables.get("ouroboros").unwrap().roll(&tables, &mut map_rng(0)),
+        Err(MapFormatError::LootTableTooDeep { .. })
+    ));
+}
+
+#[test]
+fn broken_references_are_found_on_load() {
+    let path = common::temp_path("loot_cycle.ron");
+    std::fs::write(&path, r#"{
+        "camp": (rolls: 1, entries: [
+            (source: Item("bread"), weight: 1000, min_quantity: 1, max_quantity: 1),
+            (source: Table("cellar"), weight: 1, min_quantity: 1, max_quantity: 1)
+        ]),
+        "cellar": (rolls: 0, guaranteed: [
+            (source: Table("camp"), weight: 1, min_quantity: 1, max_quantity: 1)
+        ])
+    }"#).unwrap();
+    match LootTables::read_from_path(&path) {
+        Err(MapFormatError::LootTableCycle { names }) => assert_eq!(names, vec!["camp", "cellar", "camp"]),
+        other => panic!("expected a cycle, got {:?}", other)
+    }
+
+    std::fs::write(&path, r#"{
+        "camp": (rolls: 1, entries: [
+            (source: Item("bread"), weight: 1000, min_quantity: 1, max_quantity: 1),
+            (source: Table("bandit"), weight: 1, min_quantity: 1, max_quantity: 1)
+        ])
+    }"#).unwrap();
+    assert!(matches!(
+        LootTables::read_from_path(&path),
+        Err(MapFormatError::UnknownLootTable { name }) if name == "bandit"
+    ));
+
+    let tables = shared_tables();
+    tables.validate().unwrap();
+    let component = LootTable {
+        rolls: 1,
+        guaranteed: vec![entry(LootSource::Table("wolf".to_string()), 1, 1, 1)],
+        entries: vec![entry(LootSource::Table("bandit".to_string()), 1, 1, 1)]
+    };
+    assert!(matches!(
+        tables.validate_table_references(&component),
+        Err(MapFormatError::UnknownLootTable { name }) if name == "bandit"
+    ));
+}
+
+#[test]
+fn huge_quantities_neither_overflow_nor_hang() {
+    let coins = LootTable {
+        rolls: 0,
+        guaranteed: vec![entry(item("coin"), 1, 3_000_000_000, 3_000_000_000); 2],
+        entries: Vec::new()
+    };
+    let drops = coins.roll(&LootTables::default(), &mut map_rng(0)).unwrap();
+    assert_eq!(drops, vec![LootDrop { item: "coin".to_string(), quantity: u32::MAX }]);
+
+    let tables = shared_tables();
+    let hoard = LootTable {
+        rolls: 1,
+        guaranteed: Vec::new(),
+        entries: vec![
+            entry(LootSource::Nothing, 1000, 1, 1),
+            entry(LootSource::Table("coins".to_string()), 1, 1, u32::MAX)
+        ]
+    };
+    let too_large = |result| matches!(
+        result,
+        Err(MapFormatError::LootQuantityTooLarge { name, quantity: u32::MAX }) if name == "coins"
+    );
+    assert!(too_large(tables.validate_table_references(&hoard)));
+    let mut with_hoard = tables.clone();
+    with_hoard.tables.insert("hoard".to_string(), hoard.clone());
+    assert!(too_large(with_hoard.validate()));
+
+    let always = LootTable { entries: vec![hoard.entries[1].clone()], ..hoard };
+    assert!(too_large(always.roll(&tables, &mut map_rng(0)).map(|_| ())));
+}
+
+#[test]
+fn frequencies_follow_the_weights() {
+    let table = LootTable {
+        rolls: 1,
+        guaranteed: Vec::new(),
+        entries: vec![entry(item("common"), 3, 1, 1), entry(item("rare"), 1, 2, 4), entry(LootSource::Nothing, 4, 1, 1)]
+    };
+    let frequencies = table.drop_frequencies(&LootTables::default(), &mut map_rng(9), 1000).unwrap();
+    let names: Vec<_> = frequencies.iter().map(|frequency| frequency.item.as_str()).collect();
+    assert_eq!(names, vec!["common", "rare"]);
+    assert!((frequencies[0].chance - 0.375).abs() < 0.05);
+    assert!((frequencies[1].chance - 0.125).abs() < 0.05);
+    assert!((frequencies[1].average_quantity - 3.0).abs() < 0.25);
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use rl23_map_format::{map_rng, Biome, MapInfo, MapMetadata, ResizeAnchor, TerrainKind, TilePos, ValidationIssueKind, WallKind};
+use common::{fixture_path, temp_path};
+
+#[test]
+fn old_maps_get_default_metadata() {
+    let map = MapInfo::read_from_path(&fixture_path("v5.ron")).unwrap();
+    assert_eq!(map.metadata, MapMetadata::default());
+}
+
+#[test]
+fn metadata_is_read_and_written() {
+    let map = MapInfo::read_from_path(&fixture_path("v6.ron")).unwrap();
+    assert_eq!(map.metadata.name, "Old Mill");
+    assert_eq!(map.metadata.biome, Biome::Village);
+    assert_eq!(map.metadata.music_cue.as_deref(), Some("mill_theme"));
+    assert_eq!(map.metadata.recommended_level, 2);
+    assert_eq!(map.metadata.player_spawn, Some(TilePos::new(0, 1)));
+
+    for name in ["metadata.ron", "metadata.rlmap"] {
+        let path = temp_path(name);
+        map.save_to_path(&path).unwrap();
+        assert_eq!(MapInfo::read_from_path(&path).unwrap().metadata, map.metadata);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn seed_is_recorded_and_regenerates_the_map() {
+    let map = MapInfo::create_new(16, 12, 42);
+    assert_eq!(map.metadata.seed, 42);
+    assert_eq!(map.terrain_layer, MapInfo::create_new(16, 12, 42).terrain_layer);
+    assert_ne!(map.terrain_layer, MapInfo::create_new(16, 12, 43).terrain_layer);
+
+    let old = MapInfo::read_from_path(&fixture_path("v6.ron")).unwrap();
+    assert_eq!(old.metadata.seed, 0);
+    let seeded = MapInfo::read_from_path(&fixture_path("v7.ron")).unwrap();
+    assert_eq!(seeded.metadata.seed, 42);
+}
+
+#[test]
+fn seeded_mud_does_not_depend_on_the_platform() {
+    let map = MapInfo::create_new(8, 1, 42);
+    let offsets: Vec<usize> = map.terrain_layer
+        .iter()
+        .map(|(_, terrain)| match terrain {
+            TerrainKind::Mud { offset } => *offset,
+            other => panic!("{:?} is not mud", other)
+        })
+        .collect();
+    assert_eq!(offsets, vec![2, 8, 11, 9, 5, 4, 7, 3]);
+}
+
+#[test]
+fn resize_is_reproducible_with_the_same_rng() {
+    let mut first = MapInfo::create_new(4, 4, 7);
+    let mut second = first.clone();
+    first.resize(10, 8, ResizeAnchor::Center, &mut map_rng(7)).unwrap();
+    second.resize(10, 8, ResizeAnchor::Center, &mut map_rng(7)).unwrap();
+    assert_eq!(first.terrain_layer, second.terrain_layer);
+}
+
+#[test]
+fn player_spawn_moves_with_the_map() {
+    let mut map = MapInfo::create_new(4, 4, 0);
+    map.metadata.player_spawn = Some(TilePos::new(1, 1));
+    map.resize(6, 4, ResizeAnchor::Right, &mut map_rng(0)).unwrap();
+    assert_eq!(map.metadata.player_spawn, Some(TilePos::new(3, 1)));
+    map.resize(2, 2, ResizeAnchor::TopRight, &mut map_rng(0)).unwrap();
+    assert_eq!(map.metadata.player_spawn, None);
+}
+
+#[test]
+fn validation_checks_the_player_spawn() {
+    let mut map = MapInfo::create_new(3, 3, 0);
+    map.wall_layer.set(TilePos::new(1, 1), Some(WallKind::Wood));
+    map.metadata.player_spawn = Some(TilePos::new(1, 1));
+    let kinds = |map: &MapInfo| map.validate().into_iter().map(|issue| issue.kind).collect::<Vec<_>>();
+    assert_eq!(kinds(&map), vec![ValidationIssueKind::PlayerSpawnBlocked]);
+
+    map.metadata.player_spawn = Some(TilePos::new(5, 0));
+    assert_eq!(kinds(&map), vec![ValidationIssueKind::PlayerSpawnOutOfBounds]);
+}
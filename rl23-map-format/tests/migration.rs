@@ -0,0 +1,88 @@
+mod common;
+
+use rl23_map_format::{
+    EntityComponentData,
+    Light,
+    MapEntity,
+    MapFormatError,
+    MapInfo,
+    SpawnRandomUnit,
+    TilePos,
+    Tree,
+    CURRENT_FORMAT_VERSION,
+    MAX_LIGHT_LEVEL
+};
+use common::{fixture_path, temp_path};
+
+fn assert_fixture_contents(map: &MapInfo) {
+    assert_eq!((map.width(), map.height()), (3, 2));
+    assert_eq!(map.entities.len(), 2);
+
+    let tree = map.entities.get(1).unwrap();
+    assert_eq!(tree.position(), TilePos::new(2, 0));
+    assert_eq!(tree.entity, MapEntity::Tree(Tree::Oak));
+
+    let logic = map.entities.get(0).unwrap();
+    assert_eq!(logic.position(), TilePos::new(1, 1));
+    assert_eq!(logic.entity, MapEntity::Logic);
+    assert_eq!(
+        logic.components,
+        vec![EntityComponentData::SpawnRandomUnit(SpawnRandomUnit { min_level: 1, max_level: 3 })]
+    );
+    assert_eq!(map.entities.ids_at(TilePos::new(1, 1)), &[0]);
+}
+
+#[test]
+fn every_historical_version_loads_as_current() {
+    for name in ["v0.ron", "v1.ron", "v2.ron", "v3.ron", "v4.ron", "v5.ron", "v6.ron", "v7.ron", "v8.ron"] {
+        let map = MapInfo::read_from_path(&fixture_path(name)).unwrap();
+        assert_fixture_contents(&map);
+    }
+}
+
+#[test]
+fn maps_before_lighting_are_fully_lit() {
+    let old = MapInfo::read_from_path(&fixture_path("v3.ron")).unwrap();
+    assert_eq!(old.ambient_light, MAX_LIGHT_LEVEL);
+
+    let lit = MapInfo::read_from_path(&fixture_path("v4.ron")).unwrap();
+    assert_eq!(lit.ambient_light, 6);
+    assert_eq!(
+        lit.entities.get(1).unwrap().components,
+        vec![EntityComponentData::Light(Light { radius: 2, color_index: 8, intensity: 12 })]
+    );
+}
+
+#[test]
+fn migrate_file_rewrites_old_maps_in_place() {
+    let path = temp_path("v0.ron");
+    std::fs::copy(fixture_path("v0.ron"), &path).unwrap();
+    assert_eq!(MapInfo::migrate_file(&path).unwrap(), 0);
+    assert_eq!(MapInfo::migrate_file(&path).unwrap(), CURRENT_FORMAT_VERSION);
+    assert_fixture_contents(&MapInfo::read_from_path(&path).unwrap());
+}
+
+#[test]
+fn newer_versions_are_rejected() {
+    let source = std::fs::read_to_string(fixture_path("v1.ron")).unwrap();
+    let path = temp_path("future.ron");
+    std::fs::write(&path, source.replace("format_version: 1", "format_version: 9999")).unwrap();
+    match MapInfo::read_from_path(&path) {
+        Err(MapFormatError::UnsupportedVersion { found: 9999, .. }) => {}
+        other => panic!("unexpected result: {:?}", other.map(|_| ()))
+    }
+}
+
+#[test]
+fn overflowing_sizes_are_rejected() {
+    let source = std::fs::read_to_string(fixture_path("v8.ron")).unwrap();
+    let path = temp_path("huge.ron");
+    let source = source
+        .replace("width: 3", "width: 4294967296")
+        .replace("height: 2", "height: 4294967296");
+    std::fs::write(&path, source).unwrap();
+    match MapInfo::read_from_path(&path) {
+        Err(MapFormatError::MapTooLarge { width: 4294967296, height: 4294967296 }) => {}
+        other => panic!("unexpected result: {:?}", other.map(|_| ()))
+    }
+}
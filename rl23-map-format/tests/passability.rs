@@ -0,0 +1,98 @@
+mod common;
+
+use rl23_map_format::{
+    MapInfo,
+    MovementProfile,
+    TilePos,
+    Unit,
+    UnitDefinitions,
+    BASE_MOVE_COST
+};
+use common::map_from_rows;
+
+/// Renders costs as `.` for the base cost, `x` for impassable and the cost multiplier otherwise.
+fn cost_rows(map: &MapInfo, profile: MovementProfile) -> Vec<String> {
+    let costs = map.passability(profile);
+    (0..map.height())
+        .map(|y| {
+            (0..map.width())
+                .map(|x| match *costs.get(TilePos::new(x, y)).unwrap() {
+                    None => 'x',
+                    Some(BASE_MOVE_COST) => '.',
+                    Some(cost) => char::from_digit(cost / BASE_MOVE_COST, 10).unwrap()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+const ROWS: [&str; 3] = [
+    ".#~~,",
+    ".T^,.",
+    "WD..."
+];
+
+#[test]
+fn walkers_avoid_water_lava_walls_and_blocking_entities() {
+    let map = map_from_rows(&ROWS);
+    assert_eq!(cost_rows(&map, MovementProfile::Walker), vec![
+        ".xxx3",
+        ".xx3.",
+        ".x..."
+    ]);
+}
+
+#[test]
+fn swimmers_prefer_water() {
+    let map = map_from_rows(&ROWS);
+    assert_eq!(cost_rows(&map, MovementProfile::Swimmer), vec![
+        "2x...",
+        "2xx.2",
+        "2x222"
+    ]);
+}
+
+#[test]
+fn flyers_ignore_terrain_but_not_obstacles() {
+    let map = map_from_rows(&ROWS);
+    assert_eq!(cost_rows(&map, MovementProfile::Flyer), vec![
+        ".x...",
+        ".x...",
+        ".x..."
+    ]);
+}
+
+#[test]
+fn wall_passers_go_everywhere() {
+    let map = map_from_rows(&ROWS);
+    assert_eq!(cost_rows(&map, MovementProfile::WallPasser), vec![
+        ".....",
+        ".....",
+        "....."
+    ]);
+}
+
+#[test]
+fn units_move_according_to_their_kind() {
+    let units = UnitDefinitions::default();
+    assert_eq!(Unit::Bat.movement_profile(&units), MovementProfile::Flyer);
+    assert_eq!(Unit::Gorynich.movement_profile(&units), MovementProfile::Flyer);
+    assert_eq!(Unit::Rusalka.movement_profile(&units), MovementProfile::Swimmer);
+    assert_eq!(Unit::Vodyanoy.movement_profile(&units), MovementProfile::Swimmer);
+    assert_eq!(Unit::Ghost.movement_profile(&units), MovementProfile::WallPasser);
+    assert_eq!(Unit::Wolf.movement_profile(&units), MovementProfile::Walker);
+}
+
+#[test]
+fn default_movement_matches_the_unit_definitions() {
+    let units = UnitDefinitions::default();
+    for unit in Unit::ALL {
+        assert_eq!(unit.default_movement_profile(), units.get(unit).unwrap().movement, "{:?}", unit);
+    }
+
+    let mut edited = units.clone();
+    edited.units.get_mut(&Unit::Wolf).unwrap().movement = MovementProfile::Swimmer;
+    assert_eq!(Unit::Wolf.movement_profile(&edited), MovementProfile::Swimmer);
+    edited.units.remove(&Unit::Bat);
+    assert_eq!(Unit::Bat.movement_profile(&edited), MovementProfile::Flyer);
+}
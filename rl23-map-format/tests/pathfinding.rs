@@ -0,0 +1,156 @@
+mod common;
+
+use rl23_map_format::{
+    find_path,
+    DistanceMap,
+    Grid,
+    MovementProfile,
+    PathOptions,
+    TilePos,
+    BASE_MOVE_COST
+};
+use common::map_from_rows;
+
+/// Draws `tiles` as `*` over `rows`.
+fn draw_path(rows: &[&str], tiles: &[TilePos]) -> Vec<String> {
+    rows.iter()
+        .enumerate()
+        .map(|(y, row)| {
+            row.chars()
+                .enumerate()
+                .map(|(x, c)| if tiles.contains(&TilePos::new(x, y)) { '*' } else { c })
+                .collect()
+        })
+        .collect()
+}
+
+const MAZE: [&str; 5] = [
+    ".....",
+    ".###.",
+    "...#.",
+    "##.#.",
+    "....."
+];
+
+#[test]
+fn walker_goes_around_walls() {
+    let map = map_from_rows(&MAZE);
+    let path = map
+        .find_path(TilePos::new(0, 2), TilePos::new(4, 4), PathOptions::new(MovementProfile::Walker))
+        .unwrap();
+    assert_eq!(draw_path(&MAZE, &path.tiles), vec![
+        ".....",
+        ".###.",
+        "***#.",
+        "##*#.",
+        "..***"
+    ]);
+    assert_eq!(path.cost, 6 * BASE_MOVE_COST);
+}
+
+#[test]
+fn diagonal_steps_do_not_cut_corners() {
+    let rows = [
+        "...",
+        ".#.",
+        "..."
+    ];
+    let map = map_from_rows(&rows);
+    let options = PathOptions::new(MovementProfile::Walker).with_diagonal(true);
+    let path = map.find_path(TilePos::new(0, 0), TilePos::new(2, 2), options).unwrap();
+    assert_eq!(path.tiles.len(), 5);
+
+    let open = map_from_rows(&["...", "...", "..."]);
+    let path = open.find_path(TilePos::new(0, 0), TilePos::new(2, 2), options).unwrap();
+    assert_eq!(path.tiles, vec![TilePos::new(0, 0), TilePos::new(1, 1), TilePos::new(2, 2)]);
+    assert_eq!(path.cost, 28);
+}
+
+#[test]
+fn cheap_detour_beats_expensive_terrain() {
+    let rows = [
+        ".,,,.",
+        "....."
+    ];
+    let map = map_from_rows(&rows);
+    let path = map
+        .find_path(TilePos::new(0, 0), TilePos::new(4, 0), PathOptions::new(MovementProfile::Walker))
+        .unwrap();
+    assert_eq!(draw_path(&rows, &path.tiles), vec![
+        "*,,,*",
+        "*****"
+    ]);
+
+    let path = map
+        .find_path(TilePos::new(0, 0), TilePos::new(4, 0), PathOptions::new(MovementProfile::Swimmer))
+        .unwrap();
+    assert_eq!(draw_path(&rows, &path.tiles), vec![
+        "*****",
+        "....."
+    ]);
+}
+
+#[test]
+fn unreachable_goals_have_no_path() {
+    let rows = [
+        ".#.",
+        ".#.",
+        ".#~"
+    ];
+    let map = map_from_rows(&rows);
+    let walker = PathOptions::new(MovementProfile::Walker);
+    assert_eq!(map.find_path(TilePos::new(0, 0), TilePos::new(2, 0), walker), None);
+    assert_eq!(map.find_path(TilePos::new(0, 0), TilePos::new(1, 0), walker), None);
+    assert!(map
+        .find_path(TilePos::new(0, 0), TilePos::new(2, 2), PathOptions::new(MovementProfile::WallPasser))
+        .is_some());
+}
+
+#[test]
+fn distance_map_leads_to_the_nearest_source() {
+    let map = map_from_rows(&MAZE);
+    let sources = [TilePos::new(0, 0), TilePos::new(0, 4)];
+    let distances = map.distance_map(&sources, PathOptions::new(MovementProfile::Walker));
+
+    assert_eq!(distances.distance(TilePos::new(0, 0)), Some(0));
+    assert_eq!(distances.distance(TilePos::new(1, 1)), None);
+    assert_eq!(distances.distance(TilePos::new(4, 0)), Some(4 * BASE_MOVE_COST));
+    assert_eq!(distances.distance(TilePos::new(4, 4)), Some(4 * BASE_MOVE_COST));
+    assert_eq!(distances.distance(TilePos::new(2, 2)), Some(4 * BASE_MOVE_COST));
+
+    let tiles = distances.path_from(TilePos::new(2, 2)).unwrap();
+    assert_eq!(tiles.first(), Some(&TilePos::new(2, 2)));
+    assert_eq!(tiles.len(), 5);
+    assert!(sources.contains(tiles.last().unwrap()));
+    assert_eq!(distances.next_step(TilePos::new(0, 0)), None);
+}
+
+#[test]
+fn distance_map_reaches_blocked_sources() {
+    let rows = [
+        "...",
+        ".#.",
+        "..."
+    ];
+    let map = map_from_rows(&rows);
+    let costs = map.passability(MovementProfile::Walker);
+    let distances = DistanceMap::new(costs.clone(), &[TilePos::new(1, 1)], false);
+    assert_eq!(distances.distance(TilePos::new(1, 0)), Some(BASE_MOVE_COST));
+    assert_eq!(distances.distance(TilePos::new(0, 0)), Some(2 * BASE_MOVE_COST));
+    assert_eq!(distances.next_step(TilePos::new(1, 0)), Some(TilePos::new(1, 1)));
+
+    let path = find_path(&costs, TilePos::new(0, 0), TilePos::new(2, 2), false).unwrap();
+    assert_eq!(path.cost, 4 * BASE_MOVE_COST);
+}
+
+#[test]
+fn paths_stay_cheapest_with_costs_below_the_base_cost() {
+    // A road of cost 1 along the top row, ordinary tiles everywhere else.
+    let costs = Grid::from_fn(5, 3, |pos| Some(if pos.y == 0 { 1 } else { BASE_MOVE_COST }));
+    for allow_diagonal in [false, true] {
+        let path = find_path(&costs, TilePos::new(0, 2), TilePos::new(4, 2), allow_diagonal).unwrap();
+        let around = DistanceMap::new(costs.clone(), &[TilePos::new(0, 2)], allow_diagonal);
+        assert_eq!(Some(path.cost), around.distance(TilePos::new(4, 2)), "diagonal: {}", allow_diagonal);
+        assert!(path.tiles.contains(&TilePos::new(2, 0)));
+    }
+}
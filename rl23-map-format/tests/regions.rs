@@ -0,0 +1,122 @@
+mod common;
+
+use rl23_map_format::{
+    map_rng,
+    GridBounds,
+    MapInfo,
+    Region,
+    RegionShape,
+    ResizeAnchor,
+    TilePos,
+    ValidationIssueKind
+};
+use common::{fixture_path, rect, temp_path};
+
+fn map_with_regions(shapes: Vec<(RegionShape, bool)>) -> MapInfo {
+    let mut map = MapInfo::create_new(6, 4, 0);
+    for (idx, (shape, exclusive)) in shapes.into_iter().enumerate() {
+        let mut region = Region::new(map.id_generator.generate(), format!("region {}", idx), shape);
+        region.exclusive = exclusive;
+        map.regions.push(region);
+    }
+    map
+}
+
+#[test]
+fn regions_are_queried_by_tile() {
+    let map = map_with_regions(vec![
+        (rect(0, 0, 3, 2), false),
+        (RegionShape::Tiles([TilePos::new(2, 1), TilePos::new(5, 3)].into_iter().collect()), false)
+    ]);
+    let ids = |pos| map.regions_at(pos).iter().map(|region| region.id).collect::<Vec<_>>();
+    assert_eq!(ids(TilePos::new(2, 1)), vec![0, 1]);
+    assert_eq!(ids(TilePos::new(0, 0)), vec![0]);
+    assert_eq!(ids(TilePos::new(3, 0)), Vec::<u64>::new());
+
+    let region = map.region(0).unwrap();
+    assert_eq!(map.tiles_in(region), vec![
+        TilePos::new(0, 0), TilePos::new(1, 0), TilePos::new(2, 0),
+        TilePos::new(0, 1), TilePos::new(1, 1), TilePos::new(2, 1)
+    ]);
+}
+
+#[test]
+fn painting_a_rectangle_turns_it_into_tiles() {
+    let bounds = GridBounds::new(6, 4);
+    let mut shape = rect(1, 1, 2, 1);
+    shape.insert(TilePos::new(2, 1), bounds);
+    assert_eq!(shape, rect(1, 1, 2, 1));
+
+    shape.remove(TilePos::new(1, 1), bounds);
+    shape.insert(TilePos::new(4, 0), bounds);
+    assert_eq!(shape.tiles(), vec![TilePos::new(4, 0), TilePos::new(2, 1)]);
+}
+
+#[test]
+fn huge_rectangles_are_cut_to_the_map() {
+    let mut map = map_with_regions(vec![
+        (rect(4, 2, usize::MAX, usize::MAX), false),
+        (rect(usize::MAX, 1, 2, 2), false)
+    ]);
+    assert!(map.regions[0].contains(TilePos::new(usize::MAX, 3)));
+    assert!(!map.regions[1].contains(TilePos::new(0, 1)));
+    assert_eq!(map.tiles_in(map.region(0).unwrap()), vec![
+        TilePos::new(4, 2), TilePos::new(5, 2),
+        TilePos::new(4, 3), TilePos::new(5, 3)
+    ]);
+    assert!(map.tiles_in(map.region(1).unwrap()).is_empty());
+    assert_eq!(map.validate().len(), 1);
+
+    let bounds = map.bounds();
+    map.region_mut(0).unwrap().shape.remove(TilePos::new(5, 3), bounds);
+    assert_eq!(map.tiles_in(map.region(0).unwrap()).len(), 3);
+
+    map.resize(8, 8, ResizeAnchor::BottomRight, &mut map_rng(0)).unwrap();
+    assert!(map.region(1).unwrap().shape.is_empty());
+}
+
+#[test]
+fn resizing_cuts_regions_to_the_new_bounds() {
+    let mut map = map_with_regions(vec![
+        (rect(2, 1, 4, 2), false),
+        (RegionShape::Tiles([TilePos::new(0, 0), TilePos::new(5, 3)].into_iter().collect()), false),
+        (rect(0, 0, 1, 1), false)
+    ]);
+    map.resize(4, 3, ResizeAnchor::BottomRight, &mut map_rng(0)).unwrap();
+
+    assert_eq!(map.region(0).unwrap().shape, rect(0, 0, 4, 2));
+    assert_eq!(map.tiles_in(map.region(1).unwrap()), vec![TilePos::new(3, 2)]);
+    assert!(map.region(2).unwrap().shape.is_empty());
+}
+
+#[test]
+fn validation_flags_empty_and_overlapping_exclusive_regions() {
+    let map = map_with_regions(vec![
+        (rect(0, 0, 3, 3), true),
+        (rect(2, 2, 2, 2), true),
+        (rect(0, 0, 6, 4), false),
+        (RegionShape::Tiles(Default::default()), false)
+    ]);
+    let kinds: Vec<ValidationIssueKind> = map.validate().into_iter().map(|issue| issue.kind).collect();
+    assert_eq!(kinds, vec![
+        ValidationIssueKind::OverlappingRegions { id: 0, other: 1 },
+        ValidationIssueKind::EmptyRegion { id: 3 }
+    ]);
+}
+
+#[test]
+fn regions_survive_every_storage_format() {
+    let map = MapInfo::read_from_path(&fixture_path("v5.ron")).unwrap();
+    let village = map.region(2).unwrap();
+    assert_eq!(village.name, "village");
+    assert!(village.has_tag("no_spawn"));
+    assert!(village.exclusive);
+
+    for name in ["regions.ron", "regions.rlmap"] {
+        let path = temp_path(name);
+        map.save_to_path(&path).unwrap();
+        let reloaded = MapInfo::read_from_path(&path).unwrap();
+        assert_eq!(reloaded.regions, map.regions);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
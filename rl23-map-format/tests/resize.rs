@@ -0,0 +1,94 @@
+use rl23_map_format::{
+    map_rng,
+    GatherableItem,
+    MapFormatError,
+    MapInfo,
+    ResizeAnchor,
+    TerrainKind,
+    TilePos,
+    WallKind
+};
+
+/// Grass map with a single wall, so moved tiles can be told from new ones.
+fn grass_map(width: usize, height: usize, wall: TilePos) -> MapInfo {
+    let mut map = MapInfo::create_new(width, height, 0);
+    for (_, terrain) in map.terrain_layer.iter_mut() {
+        *terrain = TerrainKind::Grass;
+    }
+    map.wall_layer.set(wall, Some(WallKind::Bricks));
+    map
+}
+
+fn walls(map: &MapInfo) -> Vec<TilePos> {
+    map.wall_layer.iter().filter(|(_, wall)| wall.is_some()).map(|(pos, _)| pos).collect()
+}
+
+#[test]
+fn anchor_keeps_its_part_of_the_map_in_place() {
+    let expected = [
+        (ResizeAnchor::TopLeft, TilePos::new(0, 0)),
+        (ResizeAnchor::Top, TilePos::new(1, 0)),
+        (ResizeAnchor::TopRight, TilePos::new(2, 0)),
+        (ResizeAnchor::Left, TilePos::new(0, 1)),
+        (ResizeAnchor::Center, TilePos::new(1, 1)),
+        (ResizeAnchor::Right, TilePos::new(2, 1)),
+        (ResizeAnchor::BottomLeft, TilePos::new(0, 2)),
+        (ResizeAnchor::Bottom, TilePos::new(1, 2)),
+        (ResizeAnchor::BottomRight, TilePos::new(2, 2))
+    ];
+    for (anchor, wall) in expected {
+        let mut map = grass_map(3, 3, TilePos::new(0, 0));
+        map.resize(5, 5, anchor, &mut map_rng(0)).unwrap();
+        assert_eq!(walls(&map), vec![wall], "{}", anchor.name());
+        assert_eq!(anchor.name().parse(), Ok(anchor));
+    }
+    assert_eq!("middle".parse::<ResizeAnchor>(), Err(()));
+}
+
+#[test]
+fn growing_fills_new_tiles_with_mud() {
+    let mut map = grass_map(2, 2, TilePos::new(1, 1));
+    let report = map.resize(4, 3, ResizeAnchor::TopLeft, &mut map_rng(0)).unwrap();
+
+    assert_eq!((map.width(), map.height()), (4, 3));
+    assert_eq!(walls(&map), vec![TilePos::new(1, 1)]);
+    for (pos, terrain) in map.terrain_layer.iter() {
+        if pos.x < 2 && pos.y < 2 {
+            assert_eq!(*terrain, TerrainKind::Grass);
+        } else {
+            assert!(matches!(terrain, TerrainKind::Mud { .. }), "{:?} at {:?}", terrain, pos);
+        }
+    }
+    assert!(report.dropped_entities.is_empty());
+    assert_eq!(report.dropped_gatherables, 0);
+}
+
+#[test]
+fn cropping_drops_what_is_cut_off() {
+    let mut map = grass_map(4, 4, TilePos::new(2, 1));
+    map.gatherable_layer.insert(TilePos::new(0, 0), GatherableItem::Wheat);
+    map.gatherable_layer.insert(TilePos::new(3, 3), GatherableItem::Wheat);
+    map.gatherable_layer.insert(TilePos::new(2, 2), GatherableItem::Mushroom(0));
+
+    let report = map.resize(2, 2, ResizeAnchor::Center, &mut map_rng(0)).unwrap();
+
+    assert_eq!((map.width(), map.height()), (2, 2));
+    assert!(map.terrain_layer.iter().all(|(_, &terrain)| terrain == TerrainKind::Grass));
+    assert_eq!(walls(&map), vec![TilePos::new(1, 0)]);
+    assert_eq!(report.dropped_gatherables, 2);
+    assert_eq!(map.gatherable_layer.get(TilePos::new(1, 1)), Some(&GatherableItem::Mushroom(0)));
+    assert_eq!(map.gatherable_layer.len(), 1);
+}
+
+#[test]
+fn sizes_without_tiles_are_rejected() {
+    let mut map = grass_map(3, 2, TilePos::new(1, 1));
+    let before = map.to_canonical_string().unwrap();
+    for (width, height) in [(0, 2), (3, 0), (0, 0)] {
+        assert!(matches!(
+            map.resize(width, height, ResizeAnchor::Center, &mut map_rng(0)),
+            Err(MapFormatError::EmptyMap { width: w, height: h }) if (w, h) == (width, height)
+        ));
+    }
+    assert_eq!(map.to_canonical_string().unwrap(), before);
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use rl23_map_format::{
+    Biome,
+    EntityComponentData,
+    MapEntity,
+    MapInfo,
+    PlacedEntity,
+    Region,
+    RegionShape,
+    SpawnPool,
+    SpawnRandomUnit,
+    SpawnResolver,
+    TilePos,
+    Unit,
+    UnitDefinitions,
+    NO_SPAWN_TAG
+};
+use common::fixture_path;
+
+fn spawner_map(biome: Biome, spawners: &[(TilePos, i32, i32)]) -> MapInfo {
+    let mut map = MapInfo::create_new(8, 8, 0);
+    map.metadata.biome = biome;
+    for &(pos, min_level, max_level) in spawners {
+        let mut placed = PlacedEntity::new(map.id_generator.generate(), pos, MapEntity::Logic);
+        placed.components.push(EntityComponentData::SpawnRandomUnit(SpawnRandomUnit { min_level, max_level }));
+        map.entities.insert(placed);
+    }
+    map
+}
+
+fn candidate_names(map: &MapInfo, pool: &SpawnPool, units: &UnitDefinitions) -> Vec<Vec<String>> {
+    let resolver = SpawnResolver::new(map, pool, units);
+    map.spawners()
+        .iter()
+        .map(|spawner| resolver.candidates(spawner).iter().map(|candidate| candidate.entry.name.clone()).collect())
+        .collect()
+}
+
+#[test]
+fn candidates_are_filtered_by_level_and_biome() {
+    let (pool, units) = (SpawnPool::default(), UnitDefinitions::default());
+    let forest = spawner_map(Biome::Forest, &[(TilePos::new(1, 1), 1, 2), (TilePos::new(5, 5), 4, 7)]);
+    assert_eq!(candidate_names(&forest, &pool, &units), vec![
+        vec!["wolf", "snakes", "squirrels", "mushroom_men", "spiders"],
+        vec!["wolf_pack", "volkolak"]
+    ]);
+
+    let dungeon = spawner_map(Biome::Dungeon, &[(TilePos::new(1, 1), 1, 6)]);
+    assert_eq!(candidate_names(&dungeon, &pool, &units), vec![
+        vec!["spiders", "bats", "skeletons", "necromancer"]
+    ]);
+}
+
+#[test]
+fn region_tags_and_map_weights_change_the_candidates() {
+    let (pool, units) = (SpawnPool::default(), UnitDefinitions::default());
+    let mut map = spawner_map(Biome::Village, &[(TilePos::new(1, 1), 1, 9), (TilePos::new(5, 5), 1, 9)]);
+    let mut graveyard = Region::new(map.id_generator.generate(), "graveyard", RegionShape::Tiles(
+        [TilePos::new(1, 1)].into_iter().collect()
+    ));
+    graveyard.tags.push("graveyard".to_string());
+    map.regions.push(graveyard);
+    assert_eq!(candidate_names(&map, &pool, &units), vec![vec!["ghosts"], Vec::<&str>::new()]);
+
+    map.metadata.spawn_weights.insert("ghosts".to_string(), 0);
+    assert_eq!(candidate_names(&map, &pool, &units), vec![Vec::<&str>::new(), Vec::new()]);
+
+    map.metadata.spawn_weights.clear();
+    map.regions[0].tags.push(NO_SPAWN_TAG.to_string());
+    assert_eq!(candidate_names(&map, &pool, &units), vec![Vec::<&str>::new(), Vec::new()]);
+}
+
+#[test]
+fn groups_are_resolved_reproducibly() {
+    let (pool, units) = (SpawnPool::default(), UnitDefinitions::default());
+    let map = spawner_map(Biome::Dungeon, &[(TilePos::new(1, 1), 6, 6), (TilePos::new(2, 2), 1, 3)]);
+    let resolver = SpawnResolver::new(&map, &pool, &units);
+
+    let spawns = resolver.resolve_all(3);
+    assert_eq!(spawns, resolver.resolve_all(3));
+
+    let necromancer = spawns[0].1.as_ref().unwrap();
+    assert_eq!(necromancer.entry, "necromancer");
+    assert_eq!(necromancer.units[0], Unit::Necromancer);
+    let skeletons = necromancer.units.iter().filter(|&&unit| unit == Unit::Skeleton1).count();
+    assert!((2..=3).contains(&skeletons));
+
+    // Every spawner rolls on its own, so the first one does not depend on the others.
+    let alone = spawner_map(Biome::Dungeon, &[(TilePos::new(1, 1), 6, 6)]);
+    let alone_spawns = SpawnResolver::new(&alone, &pool, &units).resolve_all(3);
+    assert_eq!(alone_spawns[0].1, spawns[0].1);
+}
+
+#[test]
+fn spawn_weights_are_read_from_the_metadata() {
+    let map = MapInfo::read_from_path(&fixture_path("v8.ron")).unwrap();
+    assert_eq!(map.metadata.spawn_weights.get("bandits"), Some(&12));
+    assert_eq!(map.metadata.spawn_weights.get("wolf"), Some(&0));
+    let old = MapInfo::read_from_path(&fixture_path("v7.ron")).unwrap();
+    assert!(old.metadata.spawn_weights.is_empty());
+}
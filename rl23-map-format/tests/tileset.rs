@@ -0,0 +1,63 @@
+use rl23_map_format::{
+    Autotile,
+    ClosedDoor,
+    Decor,
+    GatherableItem,
+    MapEntity,
+    TerrainKind,
+    TilingInfo,
+    Tileset,
+    Tree,
+    Unit,
+    WallKind
+};
+
+fn all_entities() -> Vec<MapEntity> {
+    let mut entities = vec![MapEntity::Door, MapEntity::Loot, MapEntity::Logic];
+    entities.extend(ClosedDoor::ALL.map(MapEntity::ClosedDoor));
+    entities.extend(Decor::ALL.map(MapEntity::Decor));
+    entities.extend(Unit::ALL.map(MapEntity::Unit));
+    entities.extend(Tree::ALL.map(MapEntity::Tree));
+    entities
+}
+
+#[test]
+fn default_tileset_matches_hard_coded_sprites() {
+    let tileset = Tileset::default();
+
+    for offset in 0..12 {
+        let expected = match (TerrainKind::Mud { offset }).get_tiling_info() {
+            TilingInfo::Mud(mud) => mud.get_variant_coords_by_offset(),
+            TilingInfo::Wang(_) => unreachable!()
+        };
+        assert_eq!(tileset.mud_coords(offset), Some(expected), "mud variant {}", offset);
+    }
+
+    for kind in TerrainKind::WANG_KINDS {
+        let expected: Autotile = match kind.get_tiling_info() {
+            TilingInfo::Wang(wang) => wang.into(),
+            TilingInfo::Mud(_) => unreachable!()
+        };
+        assert_eq!(tileset.terrain(kind), Some(expected), "{:?}", kind);
+    }
+    assert_eq!(tileset.terrain.len(), TerrainKind::WANG_KINDS.len());
+
+    for kind in WallKind::ALL {
+        let expected: Autotile = match kind.get_tiling_info() {
+            TilingInfo::Wang(wang) => wang.into(),
+            TilingInfo::Mud(_) => unreachable!()
+        };
+        assert_eq!(tileset.wall(kind), Some(expected), "{:?}", kind);
+    }
+    assert_eq!(tileset.walls.len(), WallKind::ALL.len());
+
+    let entities = all_entities();
+    for &entity in entities.iter() {
+        assert_eq!(tileset.entity(entity), Some(entity.get_draw_command()), "{:?}", entity);
+    }
+    assert_eq!(tileset.entities.len(), entities.len());
+
+    for item in (0..10).map(GatherableItem::Mushroom).chain([GatherableItem::Wheat]) {
+        assert_eq!(tileset.gatherable_coords(item), Some(item.get_coords()), "{:?}", item);
+    }
+}
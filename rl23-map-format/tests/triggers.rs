@@ -0,0 +1,94 @@
+mod common;
+
+use rl23_map_format::{
+    Activate,
+    EntityComponentData,
+    MapEntity,
+    MapInfo,
+    OnEnterTrigger,
+    PlacedEntity,
+    Region,
+    RegionShape,
+    SetFlag,
+    StartDialogue,
+    Teleport,
+    TilePos,
+    TriggerArea,
+    Unit,
+    ValidationIssueKind
+};
+use common::temp_path;
+
+fn place(map: &mut MapInfo, pos: TilePos, entity: MapEntity, components: Vec<EntityComponentData>) -> u64 {
+    let id = map.id_generator.generate();
+    let mut placed = PlacedEntity::new(id, pos, entity);
+    placed.components = components;
+    assert!(map.entities.insert(placed));
+    id
+}
+
+fn issue_kinds(map: &MapInfo) -> Vec<ValidationIssueKind> {
+    map.validate().into_iter().map(|issue| issue.kind).collect()
+}
+
+#[test]
+fn references_to_existing_targets_are_valid() {
+    let mut map = MapInfo::create_new(6, 4, 0);
+    let npc = place(&mut map, TilePos::new(4, 1), MapEntity::Unit(Unit::Leshy), Vec::new());
+    let region = map.id_generator.generate();
+    let clearing = RegionShape::Rect { min: TilePos::new(0, 0), width: 2, height: 2 };
+    map.regions.push(Region::new(region, "clearing", clearing));
+    let lever = place(&mut map, TilePos::new(1, 3), MapEntity::Logic, Vec::new());
+    place(&mut map, TilePos::new(2, 2), MapEntity::Logic, vec![
+        EntityComponentData::OnEnterTrigger(OnEnterTrigger { area: TriggerArea::Region(region), once: true }),
+        EntityComponentData::StartDialogue(StartDialogue { npc_id: npc, stage: 1 }),
+        EntityComponentData::Activate(Activate { target_id: lever }),
+        EntityComponentData::SetFlag(SetFlag { flag: "met_leshy".to_string(), value: true }),
+        EntityComponentData::Teleport(Teleport { map: String::new(), pos: TilePos::new(5, 3) })
+    ]);
+    assert_eq!(issue_kinds(&map), Vec::new());
+}
+
+#[test]
+fn dangling_references_are_reported() {
+    let mut map = MapInfo::create_new(6, 4, 0);
+    let gone = place(&mut map, TilePos::new(4, 1), MapEntity::Unit(Unit::Leshy), Vec::new());
+    place(&mut map, TilePos::new(2, 2), MapEntity::Logic, vec![
+        EntityComponentData::OnEnterTrigger(OnEnterTrigger { area: TriggerArea::Region(42), once: false }),
+        EntityComponentData::StartDialogue(StartDialogue { npc_id: gone, stage: 0 }),
+        EntityComponentData::Activate(Activate { target_id: 99 }),
+        EntityComponentData::Teleport(Teleport { map: String::new(), pos: TilePos::new(6, 0) }),
+        EntityComponentData::Teleport(Teleport { map: "cave.ron".to_string(), pos: TilePos::new(60, 0) })
+    ]);
+    map.entities.remove(gone);
+
+    let issues = map.validate();
+    assert!(issues.iter().all(|issue| issue.position == Some(TilePos::new(2, 2))));
+    assert_eq!(
+        issues.into_iter().map(|issue| issue.kind).collect::<Vec<_>>(),
+        vec![
+            ValidationIssueKind::MissingRegion { region: 42 },
+            ValidationIssueKind::MissingEntity { target: gone },
+            ValidationIssueKind::MissingEntity { target: 99 },
+            ValidationIssueKind::TeleportOutOfBounds { target: TilePos::new(6, 0) }
+        ]
+    );
+}
+
+#[test]
+fn trigger_components_are_saved_and_loaded() {
+    let mut map = MapInfo::create_new(4, 4, 0);
+    let target = place(&mut map, TilePos::new(0, 0), MapEntity::Door, Vec::new());
+    place(&mut map, TilePos::new(3, 3), MapEntity::Logic, vec![
+        EntityComponentData::OnEnterTrigger(OnEnterTrigger { area: TriggerArea::Radius(2), once: false }),
+        EntityComponentData::Activate(Activate { target_id: target }),
+        EntityComponentData::Teleport(Teleport { map: "cave.ron".to_string(), pos: TilePos::new(7, 9) })
+    ]);
+
+    for name in ["triggers.ron", "triggers.rlmap"] {
+        let path = temp_path(name);
+        map.save_to_path(&path).unwrap();
+        assert_eq!(MapInfo::read_from_path(&path).unwrap().entities, map.entities);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,49 @@
+use std::path::PathBuf;
+use rl23_map_format::{
+    LocalizedText,
+    MovementProfile,
+    Unit,
+    UnitDefinitions,
+    UnitTag,
+    DEFAULT_LANGUAGE
+};
+
+#[test]
+fn every_unit_has_a_definition() {
+    let definitions = UnitDefinitions::default();
+    assert_eq!(definitions.missing(), Vec::<Unit>::new());
+    for unit in Unit::ALL {
+        let definition = unit.definition(&definitions).unwrap();
+        assert!(!definition.name.get(DEFAULT_LANGUAGE).is_empty(), "{:?} has no name", unit);
+        assert!(!definition.name.get("ru").is_empty(), "{:?} has no russian name", unit);
+        assert!(definition.hp > 0, "{:?} has no hp", unit);
+    }
+}
+
+#[test]
+fn definitions_are_read_from_files() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../assets/units.ron");
+    let definitions = UnitDefinitions::read_from_path(&path).unwrap();
+    assert_eq!(definitions, UnitDefinitions::default());
+
+    let ghost = definitions.get(Unit::Ghost).unwrap();
+    assert_eq!(ghost.movement, MovementProfile::WallPasser);
+    assert!(ghost.has_tag(UnitTag::Undead));
+    assert!(definitions.get(Unit::Wolf).unwrap().has_tag(UnitTag::Beast));
+
+    // Definitions are written in the order units are declared, so saved files diff cleanly.
+    let written = ron::to_string(&definitions).unwrap();
+    let positions: Vec<_> = Unit::ALL
+        .iter()
+        .map(|unit| written.find(&format!("{:?}:(", unit)).unwrap())
+        .collect();
+    assert!(positions.windows(2).all(|pair| pair[0] < pair[1]), "{}", written);
+}
+
+#[test]
+fn names_fall_back_to_the_default_language() {
+    let name: LocalizedText = ron::from_str(r#"{"en": "Wolf", "ru": "Волк"}"#).unwrap();
+    assert_eq!(name.get("ru"), "Волк");
+    assert_eq!(name.get("de"), "Wolf");
+    assert_eq!(LocalizedText::default().get("ru"), "");
+}
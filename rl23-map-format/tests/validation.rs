@@ -0,0 +1,106 @@
+mod common;
+
+use rl23_map_format::{
+    EntityComponentData,
+    MapEntity,
+    MapInfo,
+    PlacedEntity,
+    Region,
+    Severity,
+    SpawnRandomUnit,
+    TilePos,
+    ValidationIssue,
+    ValidationIssueKind
+};
+use common::rect;
+
+fn spawn(min_level: i32, max_level: i32) -> EntityComponentData {
+    EntityComponentData::SpawnRandomUnit(SpawnRandomUnit { min_level, max_level })
+}
+
+#[test]
+fn valid_map_has_no_issues() {
+    let mut map = MapInfo::create_new(4, 4, 0);
+    let id = map.id_generator.generate();
+    let mut entity = PlacedEntity::new(id, TilePos::new(1, 1), MapEntity::Logic);
+    entity.components.push(spawn(2, 2));
+    map.entities.insert(entity);
+    assert!(map.validate().is_empty());
+}
+
+#[test]
+fn ids_must_come_from_the_generator() {
+    let mut map = MapInfo::create_new(4, 4, 0);
+    map.id_generator.generate();
+    map.entities.insert(PlacedEntity::new(7, TilePos::new(2, 3), MapEntity::Loot));
+    map.regions.push(Region::new(9, "cave", rect(1, 0, 1, 1)));
+
+    assert_eq!(map.validate(), vec![
+        ValidationIssue {
+            severity: Severity::Error,
+            position: Some(TilePos::new(2, 3)),
+            kind: ValidationIssueKind::IdNotGenerated { id: 7, next_id: 1 }
+        },
+        ValidationIssue {
+            severity: Severity::Error,
+            position: Some(TilePos::new(1, 0)),
+            kind: ValidationIssueKind::IdNotGenerated { id: 9, next_id: 1 }
+        }
+    ]);
+}
+
+#[test]
+fn spawn_level_range_must_not_be_inverted() {
+    let mut map = MapInfo::create_new(4, 4, 0);
+    let entity_id = map.id_generator.generate();
+    let mut entity = PlacedEntity::new(entity_id, TilePos::new(0, 2), MapEntity::Logic);
+    entity.components.push(spawn(5, 3));
+    map.entities.insert(entity);
+    let mut region = Region::new(map.id_generator.generate(), "den", rect(2, 2, 2, 2));
+    region.components.push(spawn(4, 1));
+    map.regions.push(region);
+
+    let issues = map.validate();
+    assert_eq!(issues, vec![
+        ValidationIssue {
+            severity: Severity::Error,
+            position: Some(TilePos::new(0, 2)),
+            kind: ValidationIssueKind::InvalidLevelRange { min_level: 5, max_level: 3 }
+        },
+        ValidationIssue {
+            severity: Severity::Error,
+            position: Some(TilePos::new(2, 2)),
+            kind: ValidationIssueKind::InvalidLevelRange { min_level: 4, max_level: 1 }
+        }
+    ]);
+    assert_eq!(issues[0].to_string(), "error at (0, 2): min_level 5 is greater than max_level 3");
+}
+
+#[test]
+fn issues_are_ordered_by_layer_and_id() {
+    let mut map = MapInfo::create_new(6, 4, 0);
+    let ids: Vec<u64> = (0..4).map(|_| map.id_generator.generate()).collect();
+    for (id, x) in [(ids[1], 0), (ids[0], 5)] {
+        let mut entity = PlacedEntity::new(id, TilePos::new(x, 0), MapEntity::Logic);
+        entity.components.push(spawn(2, 1));
+        map.entities.insert(entity);
+    }
+    for (id, x) in [(ids[3], 1), (ids[2], 4)] {
+        let mut region = Region::new(id, format!("region {}", id), rect(x, 3, 1, 1));
+        region.components.push(spawn(2, 1));
+        map.regions.push(region);
+    }
+    map.metadata.player_spawn = Some(TilePos::new(6, 0));
+
+    let issues = map.validate();
+    let positions: Vec<_> = issues.iter().map(|issue| issue.position).collect();
+    assert_eq!(positions, vec![
+        Some(TilePos::new(5, 0)),
+        Some(TilePos::new(0, 0)),
+        Some(TilePos::new(4, 3)),
+        Some(TilePos::new(1, 3)),
+        None
+    ]);
+    assert_eq!(issues.last().unwrap().kind, ValidationIssueKind::PlayerSpawnOutOfBounds);
+    assert_eq!(map.validate(), issues);
+}
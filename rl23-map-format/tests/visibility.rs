@@ -0,0 +1,187 @@
+mod common;
+
+use rl23_map_format::{
+    bresenham_line,
+    MapEntity,
+    TilePos,
+    Tileset,
+    Tree
+};
+use common::{find_in_rows, map_from_rows};
+
+/// Shows the tiles seen from `@` as they are and hides the others behind a space.
+fn fov_rows(rows: &[&str], radius: usize) -> Vec<String> {
+    let map = map_from_rows(rows);
+    let viewer = find_in_rows(rows, '@');
+    let visible = map.visible_tiles(&Tileset::default(), viewer, radius);
+    rows.iter()
+        .enumerate()
+        .map(|(y, row)| {
+            row.chars()
+                .enumerate()
+                .map(|(x, c)| if visible.contains(&TilePos::new(x, y)) { c } else { ' ' })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn walls_cast_shadows() {
+    assert_eq!(fov_rows(&[
+        ".........",
+        ".........",
+        "...#.....",
+        "....@....",
+        ".........",
+        "......#..",
+        "........."
+    ], 10), vec![
+        "   ......",
+        ".  ......",
+        "...#.....",
+        "....@....",
+        ".........",
+        "......#..",
+        "....... ."
+    ]);
+}
+
+#[test]
+fn radius_limits_the_view() {
+    assert_eq!(fov_rows(&[
+        ".........",
+        ".........",
+        ".........",
+        "....@....",
+        ".........",
+        ".........",
+        "........."
+    ], 2), vec![
+        "         ",
+        "   ...   ",
+        "  .....  ",
+        "  ..@..  ",
+        "  .....  ",
+        "   ...   ",
+        "         "
+    ]);
+}
+
+#[test]
+fn trees_block_sight_but_units_do_not() {
+    assert_eq!(fov_rows(&[
+        ".......",
+        "..T....",
+        ".......",
+        "...@...",
+        ".......",
+        "....W..",
+        "......."
+    ], 10), vec![
+        ".  ....",
+        "..T....",
+        ".......",
+        "...@...",
+        ".......",
+        "....W..",
+        "......."
+    ]);
+}
+
+#[test]
+fn rooms_hide_the_outside() {
+    assert_eq!(fov_rows(&[
+        "........",
+        ".######.",
+        ".#....#.",
+        ".#.@....",
+        ".#....#.",
+        ".######.",
+        "........"
+    ], 10), vec![
+        "        ",
+        " ###### ",
+        " #....# ",
+        " #.@....",
+        " #....# ",
+        " ###### ",
+        "        "
+    ]);
+}
+
+#[test]
+fn field_of_view_is_symmetric() {
+    let rows = [
+        "..#.....",
+        "....T...",
+        ".#...#..",
+        "......#.",
+        "..T.....",
+        "#...#..."
+    ];
+    let map = map_from_rows(&rows);
+    let tileset = Tileset::default();
+    let opaque = map.sight_blockers(&tileset);
+    let floor: Vec<TilePos> = map.bounds()
+        .positions()
+        .filter(|&pos| !opaque.get(pos).unwrap())
+        .collect();
+    for &a in &floor {
+        let seen_from_a = map.visible_tiles(&tileset, a, 20);
+        for &b in &floor {
+            let seen_from_b = map.visible_tiles(&tileset, b, 20);
+            assert_eq!(seen_from_a.contains(&b), seen_from_b.contains(&a), "{:?} {:?}", a, b);
+        }
+    }
+}
+
+#[test]
+fn bresenham_lines_include_both_ends() {
+    assert_eq!(bresenham_line(TilePos::new(0, 0), TilePos::new(4, 2)), vec![
+        TilePos::new(0, 0),
+        TilePos::new(1, 1),
+        TilePos::new(2, 1),
+        TilePos::new(3, 2),
+        TilePos::new(4, 2)
+    ]);
+    assert_eq!(bresenham_line(TilePos::new(2, 3), TilePos::new(2, 3)), vec![TilePos::new(2, 3)]);
+    assert_eq!(bresenham_line(TilePos::new(3, 0), TilePos::new(0, 3)).len(), 4);
+}
+
+#[test]
+fn line_of_sight_stops_at_walls_and_trees() {
+    let rows = [
+        "@....",
+        "..#..",
+        ".....",
+        "T....",
+        "....W"
+    ];
+    let map = map_from_rows(&rows);
+    let archer = find_in_rows(&rows, '@');
+    let tileset = Tileset::default();
+    assert!(!map.has_line_of_sight(&tileset, archer, TilePos::new(4, 2)));
+    assert!(map.has_line_of_sight(&tileset, archer, TilePos::new(2, 1)));
+    assert!(map.has_line_of_sight(&tileset, archer, TilePos::new(4, 0)));
+    assert!(map.has_line_of_sight(&tileset, archer, TilePos::new(0, 3)));
+    assert!(!map.has_line_of_sight(&tileset, archer, TilePos::new(0, 4)));
+    assert!(map.has_line_of_sight(&tileset, archer, TilePos::new(4, 4)));
+}
+
+#[test]
+fn tall_sprites_of_the_tileset_block_sight() {
+    let rows = ["@.T.."];
+    let map = map_from_rows(&rows);
+    let viewer = find_in_rows(&rows, '@');
+    let behind_tree = TilePos::new(4, 0);
+    let mut tileset = Tileset::default();
+    assert!(!map.has_line_of_sight(&tileset, viewer, behind_tree));
+
+    let birch = MapEntity::Tree(Tree::Birch);
+    tileset.entities.get_mut(&birch).unwrap().blocks_tiles_above = 0;
+    assert!(!birch.blocks_sight(&tileset));
+    assert!(map.has_line_of_sight(&tileset, viewer, behind_tree));
+
+    tileset.entities.remove(&birch);
+    assert!(map.has_line_of_sight(&tileset, viewer, behind_tree));
+}